@@ -1,6 +1,7 @@
 use clap::{Args, Parser, Subcommand};
 use enalang::{Ena, EnaError};
 use enalang_vm::machine::VMOptions;
+use std::process;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -112,6 +113,15 @@ struct Run {
     /// Whether to debug calls
     #[arg(long, default_value_t = false)]
     debug_calls: bool,
+    /// Seed for the random number generator
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Forbid the program from running other programs
+    #[arg(long, default_value_t = false)]
+    no_subprocess: bool,
+    /// Arguments passed to the program
+    #[arg(last = true)]
+    args: Vec<String>,
 }
 
 fn doc(d: Doc, ena: &mut enalang::Ena) -> Result<(), EnaError> {
@@ -170,16 +180,19 @@ fn run(r: Run, ena: &mut enalang::Ena) -> Result<(), EnaError> {
             ena.ir = Some(e);
         }
     }
-    ena.run(
+    ena.args = r.args;
+    let code = ena.run_with_exit_code(
         &r.main_word.unwrap_or("main".to_string()),
         VMOptions {
             debug_stack: r.debug_stack,
             enable_gc: r.gc,
             debug_gc: r.debug_gc,
             debug_calls: r.debug_calls,
+            allow_subprocess: !r.no_subprocess,
+            seed: r.seed,
         },
     )?;
-    Ok(())
+    process::exit(code)
 }
 
 fn json(j: Json, ena: &mut enalang::Ena) -> Result<(), EnaError> {
@@ -114,6 +114,7 @@ pub struct Ena {
     pub optimizer: optimizer::Optimizer,
     pub ir: Option<ir::IR>,
     pub macro_unwrapper: MacroUnwrapper,
+    pub args: Vec<String>,
 }
 
 impl Default for Ena {
@@ -136,6 +137,7 @@ impl Ena {
             compiled_files: HashMap::new(),
             ir: None,
             macro_unwrapper: MacroUnwrapper::default(),
+            args: Vec::new(),
         }
     }
 
@@ -427,7 +429,9 @@ impl Ena {
     }
 
     pub fn run(&mut self, main: &str, options: vm::machine::VMOptions) -> Result<(), EnaError> {
-        self.vm = Some(vm::machine::VM::new(options));
+        let mut vm = vm::machine::VM::new(options);
+        vm.args = self.args.iter().map(|x| x.to_local_str()).collect();
+        self.vm = Some(vm);
         let ir = match self.ir {
             Some(ref mut i) => i,
             None => {
@@ -455,6 +459,26 @@ impl Ena {
         self.run("main", options)
     }
 
+    // the exit code comes from `ena.vm.os.exit`, or from an integer left on the stack
+    pub fn run_with_exit_code(
+        &mut self,
+        main: &str,
+        options: vm::machine::VMOptions,
+    ) -> Result<i32, EnaError> {
+        let code = match self.run(main, options) {
+            Ok(()) => match self.vm.as_ref().and_then(|vm| vm.stack.last()) {
+                Some(ir::Value::Integer(code)) => i32::try_from(*code).unwrap_or(1),
+                _ => 0,
+            },
+            Err(EnaError::VMError(vm::machine::VMError::Exit(code))) => code,
+            Err(err) => return Err(err),
+        };
+
+        // dropping the vm closes open files
+        self.vm = None;
+        Ok(code)
+    }
+
     pub fn clean(&mut self) {
         self.tokenizer.clean();
         self.ast.clean();
@@ -0,0 +1,41 @@
+use enalang::{
+    ir,
+    vm::{
+        blocks::Blocks,
+        machine::{VMOptions, VM},
+        native,
+        vmio::MemoryIO,
+    },
+    Ena,
+};
+use flexstr::ToLocalStr;
+use std::rc::Rc;
+
+fn compile(program: &str) -> ir::IR {
+    let mut ena = Ena::new();
+    let std = concat!(env!("CARGO_MANIFEST_DIR"), "/../std/*.ena");
+    let ena_exec = concat!(env!("CARGO_MANIFEST_DIR"), "/../std/build/ena_exec.ena");
+    ena.read_files(&[std.to_string(), ena_exec.to_string()])
+        .unwrap();
+    ena.files
+        .insert("main.ena".to_string(), program.to_string());
+    ena.parse_files().unwrap();
+    ena.compile_files().unwrap();
+    ena.link_files().unwrap();
+    ena.ir.unwrap()
+}
+
+// the build script runs the same executable, instead of whatever `ena` is in PATH
+#[test]
+fn ena_path_is_the_running_executable() {
+    let io = Rc::new(MemoryIO::new(""));
+    let mut vm = VM::new(VMOptions::new());
+    vm.set_io(io.clone());
+    let program = "main { ena.corgi.ena_path println ena.executable_path println }";
+    let blocks = Blocks::new(native::group(), compile(program)).unwrap();
+
+    vm.run(&"main".to_local_str(), blocks).unwrap();
+    let exe = std::env::current_exe().unwrap();
+    let exe = exe.to_str().unwrap();
+    assert_eq!(io.stdout_contents(), format!("{exe}\n{exe}\n"));
+}
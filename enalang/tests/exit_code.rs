@@ -0,0 +1,40 @@
+use enalang::{vm::machine::VMOptions, Ena};
+
+fn run(program: &str, args: &[&str]) -> i32 {
+    let mut ena = Ena::new();
+    let std = concat!(env!("CARGO_MANIFEST_DIR"), "/../std/*.ena");
+    ena.read_files(&[std.to_string()]).unwrap();
+    ena.files
+        .insert("main.ena".to_string(), program.to_string());
+    ena.args = args.iter().map(|x| x.to_string()).collect();
+    ena.parse_files().unwrap();
+    ena.compile_files().unwrap();
+    ena.link_files().unwrap();
+    ena.run_with_exit_code("main", VMOptions::new()).unwrap()
+}
+
+#[test]
+fn args_reach_the_program() {
+    let program = r#"
+main {
+    ena.args 2 == ! if { 10 ena.vm.os.exit }
+    "second" == ! if { 11 ena.vm.os.exit }
+    "first" == ! if { 12 ena.vm.os.exit }
+    3
+}
+"#;
+    assert_eq!(run(program, &["first", "second"]), 3);
+    assert_eq!(run(program, &["first"]), 10);
+    assert_eq!(run(program, &["first", "other"]), 11);
+}
+
+#[test]
+fn exit_codes() {
+    assert_eq!(run("main { 7 ena.vm.os.exit 8 }", &[]), 7);
+    assert_eq!(run("main { 42 }", &[]), 42);
+    assert_eq!(run(r#"main { "not a code" }"#, &[]), 0);
+    assert_eq!(run("main { }", &[]), 0);
+    // codes that do not fit are a failure, instead of wrapping around to success
+    assert_eq!(run("main { 4294967296 }", &[]), 1);
+    assert_eq!(run("main { 4294967296 ena.vm.os.exit }", &[]), 1);
+}
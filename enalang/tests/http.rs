@@ -0,0 +1,164 @@
+use enalang::{
+    ir,
+    vm::machine::{VMError, VMOptions},
+    EnaError,
+};
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    thread::{self, JoinHandle},
+};
+
+// sends its address to `{report}`, then serves until a request fails
+const SERVER: &str = r#"
+hello {
+    drop
+    200 "Hello!" ena.http.response
+}
+
+inject {
+    drop
+    response ( unit )
+    headers ( unit )
+    200 "ok" ena.http.response response =
+    map.new headers =
+    "a\r\nSet-Cookie: b" "X-Test" headers @ map.insert
+    headers @ "headers" response @ map.insert
+    response @
+}
+
+main {
+    listener ( unit )
+    "127.0.0.1:0" net.listen listener =
+
+    report ( unit )
+    "{report}" net.connect report =
+    listener @ net.local_addr "{}\n" string.format report @ net.write
+    report @ net.close
+
+    routes ( unit )
+    map.new routes =
+    'hello "/hello" routes @ map.insert
+    'inject "/inject" routes @ map.insert
+    routes @ listener @ ena.http.serve
+}
+"#;
+
+// errors hold `Rc`s, so only the message of an exception leaves the thread
+fn serve(report: String) -> Result<(), String> {
+    match run(report) {
+        Err(EnaError::VMError(VMError::RuntimeException(ir::Value::String(message)))) => {
+            Err(message.to_string())
+        }
+        Err(err) => panic!("{err}"),
+        Ok(()) => Ok(()),
+    }
+}
+
+fn run(report: String) -> Result<(), EnaError> {
+    let mut ena = enalang::Ena::new();
+    let std = concat!(env!("CARGO_MANIFEST_DIR"), "/../std/*.ena");
+    ena.read_files(&[std.to_string()])?;
+    ena.files.insert(
+        "server.ena".to_string(),
+        SERVER.replace("{report}", &report),
+    );
+    ena.parse_files()?;
+    ena.compile_files()?;
+    ena.link_files()?;
+    ena.run_main(VMOptions::new())
+}
+
+// starts a server in another thread and returns its address
+fn start() -> (String, JoinHandle<Result<(), String>>) {
+    let report = TcpListener::bind("127.0.0.1:0").unwrap();
+    let report_address = report.local_addr().unwrap().to_string();
+    let server = thread::spawn(move || serve(report_address));
+
+    let mut address = String::new();
+    report
+        .accept()
+        .unwrap()
+        .0
+        .read_to_string(&mut address)
+        .unwrap();
+    (address.trim().to_string(), server)
+}
+
+fn request(address: &str, message: &str) -> String {
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(message.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+// the server may stop reading before everything is written
+fn send(address: &str, message: &[u8]) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    let _ = stream.write_all(message);
+}
+
+#[test]
+fn serve_answers_and_rejects_huge_bodies() {
+    let (address, server) = start();
+
+    let response = request(&address, "GET /hello HTTP/1.1\r\nHost: ena\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.ends_with("Hello!"), "{response}");
+
+    let response = request(&address, "GET /missing HTTP/1.1\r\n\r\n");
+    assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+
+    // the announced body is never allocated, the request fails with an exception
+    send(
+        &address,
+        b"POST /hello HTTP/1.1\r\nContent-Length: 99999999999999\r\n\r\n",
+    );
+    assert_eq!(server.join().unwrap(), Err("body is too large".to_string()));
+}
+
+#[test]
+fn serve_rejects_long_header_lines() {
+    let (address, server) = start();
+    let mut message = b"GET /hello HTTP/1.1\r\nX-Long: ".to_vec();
+    message.extend(vec![b'a'; 100_000]);
+    send(&address, &message);
+    assert_eq!(server.join().unwrap(), Err("line is too long".to_string()));
+}
+
+#[test]
+fn serve_rejects_too_many_headers() {
+    let (address, server) = start();
+    let mut message = String::from("GET /hello HTTP/1.1\r\n");
+    for i in 0..1000 {
+        message.push_str(&format!("X-Header-{i}: {i}\r\n"));
+    }
+    message.push_str("\r\n");
+    send(&address, message.as_bytes());
+    assert_eq!(server.join().unwrap(), Err("too many headers".to_string()));
+}
+
+#[test]
+fn chunked_trailers_are_limited() {
+    let (address, server) = start();
+    let mut message =
+        String::from("POST /hello HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nhi\r\n0\r\n");
+    for i in 0..1000 {
+        message.push_str(&format!("X-Trailer-{i}: {i}\r\n"));
+    }
+    message.push_str("\r\n");
+    send(&address, message.as_bytes());
+    assert_eq!(server.join().unwrap(), Err("too many headers".to_string()));
+}
+
+#[test]
+fn responses_cannot_split_headers() {
+    let (address, server) = start();
+    let response = request(&address, "GET /inject HTTP/1.1\r\n\r\n");
+    assert!(!response.contains("Set-Cookie"), "{response}");
+    assert_eq!(
+        server.join().unwrap(),
+        Err("invalid header value `a\\r\\nSet-Cookie: b`".to_string())
+    );
+}
@@ -0,0 +1,67 @@
+use enalang::{
+    ir,
+    vm::{
+        blocks::Blocks,
+        machine::{VMError, VMOptions, VM},
+        native,
+        vmio::MemoryIO,
+    },
+    Ena,
+};
+use flexstr::ToLocalStr;
+use std::rc::Rc;
+
+const PROGRAM: &str = r#"
+fails {
+    "oops" into_exception throw
+}
+
+main {
+    ena.vm.io.read_line "Hello, {}!" string.format println
+    "careful" eprintln
+    'fails try unwrap_exception println
+    'term.size try unwrap_exception println
+    term.terminal? println
+    "for real" into_exception throw
+}
+"#;
+
+fn compile(program: &str) -> ir::IR {
+    let mut ena = Ena::new();
+    let std = concat!(env!("CARGO_MANIFEST_DIR"), "/../std/*.ena");
+    ena.read_files(&[std.to_string()]).unwrap();
+    ena.files
+        .insert("main.ena".to_string(), program.to_string());
+    ena.parse_files().unwrap();
+    ena.compile_files().unwrap();
+    ena.link_files().unwrap();
+    ena.ir.unwrap()
+}
+
+#[test]
+fn memory_io_captures_everything() {
+    let io = Rc::new(MemoryIO::new("ena\n"));
+    let mut vm = VM::new(VMOptions {
+        debug_calls: true,
+        ..VMOptions::new()
+    });
+    vm.set_io(io.clone());
+    let blocks = Blocks::new(native::group(), compile(PROGRAM)).unwrap();
+
+    let result = vm.run(&"main".to_local_str(), blocks);
+    assert!(matches!(
+        result,
+        Err(VMError::RuntimeException(ir::Value::String(message))) if message == "for real"
+    ));
+    assert_eq!(
+        io.stdout_contents(),
+        "Hello, ena!\noops\nterm: not a terminal\nfalse\n"
+    );
+    assert_eq!(io.stderr_contents(), "careful\n");
+    let diagnostics = io.diagnostics_contents();
+    assert!(diagnostics.contains("CALL_DEBUG: fails\n"), "{diagnostics}");
+    assert!(
+        diagnostics.contains("CALL_DEBUG: term.size\n"),
+        "{diagnostics}"
+    );
+}
@@ -10,7 +10,10 @@ macro_rules! unreachable {
 }
 
 #[derive(Debug, thiserror::Error)]
-pub enum CGenError {}
+pub enum CGenError {
+    #[error("byte strings are not supported by the c backend")]
+    BytesNotSupported,
+}
 
 pub const NATIVE_CALL_PREFIX: &str = "ena.c.";
 pub enum CCall {
@@ -19,10 +22,12 @@ pub enum CCall {
     PutValue(enalang_ir::Value),
 }
 
-pub fn into_c(val: enalang_ir::Value) -> String {
-    match val {
+pub fn into_c(val: enalang_ir::Value) -> Result<String, CGenError> {
+    Ok(match val {
         Value::Pointer(ptr) => format!("PUSH_VALUE_POINTER({ptr});"),
         Value::Number(num) => format!("PUSH_VALUE_NUMBER({num});"),
+        Value::Integer(num) => format!("PUSH_VALUE_NUMBER({num});"),
+        Value::BigInteger(num) => format!("PUSH_VALUE_NUMBER({num}.0);"),
         Value::Boolean(b) => {
             if b {
                 String::from("PUSH_VALUE_BOOLEAN(true);")
@@ -33,23 +38,32 @@ pub fn into_c(val: enalang_ir::Value) -> String {
         Value::Null => String::from("PUSH_VALUE_NULL;"),
         Value::Atom(atom) => format!("PUSH_VALUE_ATOM({atom:?});"),
         Value::String(str) => format!("PUSH_VALUE_STRING({str:?});"),
+        Value::Bytes(_) => return Err(CGenError::BytesNotSupported),
         Value::Block(block) => format!("PUSH_VALUE_BLOCK({block:?});"),
         // exceptions can't be created at compile-time
         // since exceptions are always heap allocated,
         // it is problematic to implement them at compile-time
         Value::Exception(_) => unreachable!(),
-    }
+        // weak pointers, collections, handles and host objects are only produced by the vm at runtime
+        Value::WeakPointer(_)
+        | Value::Array(_)
+        | Value::Map(_)
+        | Value::Handle(_)
+        | Value::Host(_) => unreachable!(),
+    })
 }
 
-impl Into<String> for CCall {
-    fn into(self) -> String {
-        let a: String = match self {
+impl TryFrom<CCall> for String {
+    type Error = CGenError;
+
+    fn try_from(call: CCall) -> Result<Self, Self::Error> {
+        let a: String = match call {
             CCall::Mangled(name) => name.to_string(),
             CCall::Native(name) => name.chars().skip(NATIVE_CALL_PREFIX.len()).collect(),
-            CCall::PutValue(val) => into_c(val),
+            CCall::PutValue(val) => into_c(val)?,
         };
 
-        format!("{a}();")
+        Ok(format!("{a}();"))
     }
 }
 
@@ -0,0 +1,35 @@
+use enalang_cgen::gen::{CCall, CGenError};
+use enalang_compiler::{ast::ASTBuilder, irgen::IRGen, tok::Tokenizer};
+use enalang_ir::{IRCode, Value};
+
+fn compile(program: &str) -> Vec<Result<String, CGenError>> {
+    let mut tokenizer = Tokenizer::new();
+    let tokens = tokenizer.parse(program).unwrap().clone();
+    let ast = ASTBuilder::new().parse(&tokens).unwrap();
+    let mut irgen = IRGen::new();
+    let ir = irgen.compile(&ast).unwrap();
+    ir.blocks["main"]
+        .code
+        .iter()
+        .filter_map(|code| match code {
+            IRCode::PutValue(value) => Some(String::try_from(CCall::PutValue(value.clone()))),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn byte_literals_are_an_error() {
+    let values = compile(r#"main { "text" b"\x00\xff" }"#);
+    assert_eq!(values.len(), 2);
+    assert!(values[0].is_ok());
+    assert!(matches!(values[1], Err(CGenError::BytesNotSupported)));
+}
+
+#[test]
+fn values_become_pushes() {
+    assert_eq!(
+        enalang_cgen::gen::into_c(Value::Boolean(true)).unwrap(),
+        "PUSH_VALUE_BOOLEAN(true);"
+    );
+}
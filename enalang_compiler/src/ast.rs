@@ -7,7 +7,9 @@ pub enum ASTNodeInner {
     EscapedIdentifier(String),
     Keyword(tok::KeywordType),
     Number(f64),
+    Integer(enalang_ir::BigInt),
     String(String),
+    Bytes(Vec<u8>),
     Atom(String),
     Comment(String),
     Closer,
@@ -104,8 +106,14 @@ impl ASTBuilder {
             tok::TokenInner::String(str) => {
                 Ok(ASTNode(self.at, ASTNodeInner::String(str.to_string())))
             }
+            tok::TokenInner::Bytes(bytes) => {
+                Ok(ASTNode(self.at, ASTNodeInner::Bytes(bytes.clone())))
+            }
             tok::TokenInner::Atom(str) => Ok(ASTNode(self.at, ASTNodeInner::Atom(str.to_string()))),
             tok::TokenInner::Number(num) => Ok(ASTNode(self.at, ASTNodeInner::Number(*num))),
+            tok::TokenInner::Integer(num) => {
+                Ok(ASTNode(self.at, ASTNodeInner::Integer(num.clone())))
+            }
             tok::TokenInner::Keyword(kw) => Ok(ASTNode(self.at, ASTNodeInner::Keyword(*kw))),
             block => {
                 let typ = match block {
@@ -224,9 +224,15 @@ impl<'a> IRGen {
                 ast::ASTNodeInner::String(str) => {
                     code.push(ir::IRCode::PutValue(ir::Value::String(Into::into(str))))
                 }
+                ast::ASTNodeInner::Bytes(bytes) => {
+                    code.push(ir::IRCode::PutValue(ir::Value::Bytes(bytes.clone())))
+                }
                 ast::ASTNodeInner::Number(num) => {
                     code.push(ir::IRCode::PutValue(ir::Value::Number(*num)));
                 }
+                ast::ASTNodeInner::Integer(num) => {
+                    code.push(ir::IRCode::PutValue(ir::Value::integer(num.clone())));
+                }
                 ast::ASTNodeInner::Block(typ, _) => {
                     let nested_name = Self::get_random_name(&name);
 
@@ -1,8 +1,11 @@
+use enalang_ir as ir;
+
 pub const ONCE_OPEN: char = '(';
 pub const ONCE_CLOSE: char = ')';
 pub const UNIQUE_OPEN: char = '{';
 pub const UNIQUE_CLOSE: char = '}';
 pub const STRING_QUOTES: char = '"';
+pub const BYTES_PREFIX: char = 'b';
 pub const ESCAPE_CHAR: char = '\'';
 pub const ATOM_CHAR: char = ':';
 pub const COMMENT_SYMBOL: char = '#';
@@ -49,7 +52,9 @@ pub enum TokenInner {
     Atom(String),
     Comment(String),
     String(String),
+    Bytes(Vec<u8>),
     Number(f64),
+    Integer(ir::BigInt),
     Keyword(KeywordType),
     OnceOpen,
     OnceClose,
@@ -211,6 +216,10 @@ impl Tokenizer {
             } else if c == UNIQUE_CLOSE {
                 self.tokens.push(Token(self.at, TokenInner::UniqueClose));
                 self.at += 1;
+            } else if c == BYTES_PREFIX && en.get(self.at + 1) == Some(&STRING_QUOTES) {
+                if let Some(err) = self.parse_bytes(&en) {
+                    return Err(err);
+                }
             } else if is_id_beginning(c) {
                 if let Some(err) = self.parse_id(&en, IdentifierType::Regular) {
                     return Err(err);
@@ -319,7 +328,14 @@ impl Tokenizer {
             }
         }
 
-        let token = Token(begin, TokenInner::Number(str.parse::<f64>().unwrap()));
+        let token = if had_dot {
+            Token(begin, TokenInner::Number(str.parse::<f64>().unwrap()))
+        } else {
+            Token(
+                begin,
+                TokenInner::Integer(str.parse::<ir::BigInt>().unwrap()),
+            )
+        };
 
         self.tokens.push(token);
         self.at += 1;
@@ -382,14 +398,42 @@ impl Tokenizer {
 
     fn parse_str(&mut self, en: &[char]) -> Option<TokenizerError> {
         let begin = self.at;
-        let mut str = String::new();
+
+        match self.parse_quoted(en, false) {
+            Ok(data) => {
+                let str = String::from_utf8_lossy(&data).into_owned();
+                self.tokens.push(Token(begin, TokenInner::String(str)));
+                None
+            }
+            Err(err) => Some(err),
+        }
+    }
+
+    fn parse_bytes(&mut self, en: &[char]) -> Option<TokenizerError> {
+        let begin = self.at;
+        self.at += 1;
+
+        match self.parse_quoted(en, true) {
+            Ok(data) => {
+                self.tokens.push(Token(begin, TokenInner::Bytes(data)));
+                None
+            }
+            Err(err) => Some(err),
+        }
+    }
+
+    // reads a quoted literal as utf-8. `\x` escapes are only allowed in byte strings
+    fn parse_quoted(&mut self, en: &[char], bytes: bool) -> Result<Vec<u8>, TokenizerError> {
+        let begin = self.at;
+        let mut data = Vec::new();
+        let mut buf = [0; 4];
         self.at += 1;
 
         loop {
             let c = match en.get(self.at) {
                 Some(ch) => *ch,
                 None => {
-                    return Some(TokenizerError(begin, TokenizerErrorInner::UnclosedString));
+                    return Err(TokenizerError(begin, TokenizerErrorInner::UnclosedString));
                 }
             };
 
@@ -402,7 +446,7 @@ impl Tokenizer {
                     let next = match en.get(self.at) {
                         Some(ch) => *ch,
                         None => {
-                            return Some(TokenizerError(
+                            return Err(TokenizerError(
                                 self.at,
                                 TokenizerErrorInner::InvalidEscape,
                             ));
@@ -410,28 +454,37 @@ impl Tokenizer {
                     };
 
                     if next == '\\' {
-                        str.push('\\');
+                        data.push(b'\\');
                     } else if next == 'n' {
-                        str.push('\n');
+                        data.push(b'\n');
                     } else if next == 'r' {
-                        str.push('\r');
+                        data.push(b'\r');
                     } else if next == 't' {
-                        str.push('\t');
+                        data.push(b'\t');
                     } else if next == STRING_QUOTES {
-                        str.push(STRING_QUOTES);
+                        data.push(b'"');
+                    } else if next == 'x' && bytes {
+                        let hex: String = en.iter().skip(self.at + 1).take(2).collect();
+                        if hex.len() != 2 || !hex.chars().all(|x| x.is_ascii_hexdigit()) {
+                            return Err(TokenizerError(
+                                self.at,
+                                TokenizerErrorInner::InvalidEscape,
+                            ));
+                        }
+                        data.push(u8::from_str_radix(&hex, 16).unwrap());
+                        self.at += 2;
                     } else {
-                        return Some(TokenizerError(self.at, TokenizerErrorInner::InvalidEscape));
+                        return Err(TokenizerError(self.at, TokenizerErrorInner::InvalidEscape));
                     }
                 } else {
-                    str.push(c);
+                    data.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
                 }
 
                 self.at += 1
             }
         }
 
-        self.tokens.push(Token(begin, TokenInner::String(str)));
-        None
+        Ok(data)
     }
 }
 
@@ -1,11 +1,14 @@
 use flexstr::LocalStr;
 use flexstr::ToLocalStr;
+use num_traits::FromPrimitive;
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::hash::Hash;
 use std::hash::Hasher;
 
+pub use num_bigint::BigInt;
+
 #[derive(Debug, thiserror::Error)]
 pub enum IRError {
     #[error("block already exists - `{0}`")]
@@ -178,9 +181,17 @@ impl<'a> IRSerializable<'a> {
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum Value {
     Number(f64),
+    Integer(i64),
+    BigInteger(BigInt),
     String(LocalStr),
+    Bytes(Vec<u8>),
     Boolean(bool),
     Pointer(usize),
+    WeakPointer(usize),
+    Array(usize),
+    Map(usize),
+    Handle(usize),
+    Host(usize),
     Block(LocalStr),
     Exception(Box<Value>),
     Atom(LocalStr),
@@ -188,16 +199,45 @@ pub enum Value {
 }
 
 impl Value {
+    pub fn integer(value: BigInt) -> Self {
+        match i64::try_from(&value) {
+            Ok(i) => Value::Integer(i),
+            Err(_) => Value::BigInteger(value),
+        }
+    }
+
+    pub fn as_heap_pointer(&self) -> Option<usize> {
+        match self {
+            Value::Pointer(p)
+            | Value::Array(p)
+            | Value::Map(p)
+            | Value::Handle(p)
+            | Value::Host(p) => Some(*p),
+            _ => None,
+        }
+    }
+
     pub fn get_hash(&self) -> Option<u64> {
         let mut hasher = DefaultHasher::new();
 
         match self {
-            Value::Number(_) => {
-                return None;
+            Value::Number(n) => {
+                // integral numbers hash the same way as integers, since they compare equal
+                if n.fract() == 0.0 {
+                    return BigInt::from_f64(*n).and_then(|x| Value::integer(x).get_hash());
+                }
+                n.to_bits().hash(&mut hasher)
             }
+            Value::Integer(i) => i.hash(&mut hasher),
+            Value::BigInteger(i) => i.hash(&mut hasher),
             Value::String(str) => str.hash(&mut hasher),
+            Value::Bytes(bytes) => bytes.hash(&mut hasher),
             Value::Boolean(b) => b.hash(&mut hasher),
             Value::Pointer(p) => p.hash(&mut hasher),
+            Value::WeakPointer(w) => w.hash(&mut hasher),
+            Value::Array(p) | Value::Map(p) | Value::Handle(p) | Value::Host(p) => {
+                p.hash(&mut hasher)
+            }
             Value::Block(l) => l.hash(&mut hasher),
             Value::Exception(e) => return e.get_hash(),
             Value::Atom(a) => a.hash(&mut hasher),
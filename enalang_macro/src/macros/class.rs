@@ -30,7 +30,7 @@ impl Macro for ClassMacro {
         res.extend(vec![
             TokenInner::Identifier(format!("{name}.allocate")),
             TokenInner::UniqueOpen,
-            TokenInner::Number(fields.len() as f64),
+            TokenInner::Integer(fields.len().into()),
             TokenInner::Identifier("alloc".into()),
             TokenInner::UniqueClose,
         ]);
@@ -39,7 +39,7 @@ impl Macro for ClassMacro {
             res.extend(vec![
                 TokenInner::Identifier(format!("{name}.{field}")),
                 TokenInner::UniqueOpen,
-                TokenInner::Number(n as f64),
+                TokenInner::Integer(n.into()),
                 TokenInner::Identifier("+".into()),
                 TokenInner::Identifier("@".into()),
                 TokenInner::UniqueClose,
@@ -49,7 +49,7 @@ impl Macro for ClassMacro {
                 TokenInner::Identifier(format!("{name}.{field}=")),
                 TokenInner::UniqueOpen,
                 TokenInner::Identifier("swap".into()),
-                TokenInner::Number(n as f64),
+                TokenInner::Integer(n.into()),
                 TokenInner::Identifier("+".into()),
                 TokenInner::Identifier("=".into()),
                 TokenInner::UniqueClose,
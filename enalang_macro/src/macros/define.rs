@@ -50,8 +50,12 @@ impl Macro for DefineMacro {
                 at: 0,
             });
         };
-        let arg_count = if let Token(_, TokenInner::Number(id)) = tokens.remove(0) {
-            id as usize
+        let arg_count = match tokens.remove(0) {
+            Token(_, TokenInner::Integer(id)) => usize::try_from(&id).ok(),
+            _ => None,
+        };
+        let arg_count = if let Some(id) = arg_count {
+            id
         } else {
             return Err(MacroError::InternalMacroError {
                 name: local_str!("define"),
@@ -0,0 +1,123 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, spanned::Spanned, FnArg, ItemFn, LitStr, Type};
+
+// turns a typed rust function into a native handler.
+//
+//     #[ena_native(name = "string.len")]
+//     fn len(s: LocalStr) -> i64 { ... }
+//
+// the first argument is popped from the top of the stack, the second from below it and so on.
+// arguments of type `&mut VM` are given the vm instead, other references are rejected. `ir::Value` arguments keep their
+// reference until the function returns. the return value is pushed, a
+// `Result` is pushed when it is `Ok` and returned otherwise. the function keeps its name as
+// the handler, and `register_<name>` adds it to a `NativeGroup` under `name`, which defaults
+// to the name of the function
+#[proc_macro_attribute]
+pub fn ena_native(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut name: Option<LitStr> = None;
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("name") {
+            name = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported ena_native property"))
+        }
+    });
+    parse_macro_input!(attr with parser);
+
+    let item = parse_macro_input!(item as ItemFn);
+    match expand(name, item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+// only the last segment is checked, so `VM` and `machine::VM` both work
+fn is_vm(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "VM"),
+        _ => false,
+    }
+}
+
+fn expand(name: Option<LitStr>, item: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &item.sig;
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            sig.generics.span(),
+            "natives cannot be generic",
+        ));
+    }
+    if let Some(asyncness) = sig.asyncness {
+        return Err(syn::Error::new(asyncness.span(), "natives cannot be async"));
+    }
+
+    let mut pops = Vec::new();
+    let mut args = Vec::new();
+    for (i, input) in sig.inputs.iter().enumerate() {
+        let ty = match input {
+            FnArg::Typed(pat) => &pat.ty,
+            FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new(receiver.span(), "natives cannot take self"))
+            }
+        };
+
+        match &**ty {
+            Type::Reference(reference)
+                if reference.mutability.is_some() && is_vm(&reference.elem) =>
+            {
+                args.push(quote! { &mut *vm });
+            }
+            Type::Reference(_) => {
+                return Err(syn::Error::new(
+                    input.span(),
+                    "natives can only take `&mut VM` by reference",
+                ))
+            }
+            _ => {
+                let arg = format_ident!("arg{}", i);
+                pops.push(quote! {
+                    let #arg = ::enalang_vm::convert::pop_arg::<#ty>(&mut *vm, held)?;
+                });
+                args.push(quote! { #arg });
+            }
+        }
+    }
+
+    let ident = &sig.ident;
+    let register = format_ident!("register_{}", ident);
+    let name = name.unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+    let attrs = &item.attrs;
+    let vis = &item.vis;
+    let inner_sig = syn::Signature {
+        ident: format_ident!("inner"),
+        ..sig.clone()
+    };
+    let block = &item.block;
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis fn #ident(
+            ctx: ::enalang_vm::native::NativeHandlerCtx,
+        ) -> ::core::result::Result<(), ::enalang_vm::machine::VMError> {
+            #inner_sig #block
+
+            ::enalang_vm::convert::run_native(ctx.vm, |vm, held| {
+                #(#pops)*
+                let result = inner(#(#args),*);
+                ::enalang_vm::convert::PushValue::push_value(result, vm)
+            })
+        }
+
+        #vis fn #register(
+            group: &mut ::enalang_vm::native::NativeGroup,
+        ) -> ::core::result::Result<(), ::enalang_vm::enalang_ir::IRError> {
+            group.add_native(#name, #ident)
+        }
+    })
+}
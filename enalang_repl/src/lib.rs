@@ -11,10 +11,7 @@ use enalang_vm::{
 };
 use flexstr::{local_fmt, local_str, IntoLocalStr};
 use rand::distributions::{Alphanumeric, DistString};
-use std::{
-    io::{self, stdout, Write},
-    process,
-};
+use std::{fmt, io, process};
 
 pub const DEFAULT_OUTPUT_LENGTH: usize = 10;
 const VERSION: Option<&str> = option_env!("CARGO_PKG_VERSION");
@@ -90,8 +87,13 @@ impl Repl {
         }
     }
 
+    // the repl talks through the vm streams, so it can be driven by a `vmio::MemoryIO`
+    fn output(&self, text: impl fmt::Display) {
+        let _ = writeln!(self.vm.io().stdout(), "{text}");
+    }
+
     pub fn run_interactive(&mut self) -> ! {
-        println!("{}", get_startup_message());
+        self.output(get_startup_message());
         self.vm
             .run(
                 &local_str!("nop"),
@@ -100,26 +102,31 @@ impl Repl {
             .unwrap();
         let mut s = String::new();
         loop {
-            print!(">>> ");
-            stdout().flush().unwrap();
+            {
+                let mut stdout = self.vm.io().stdout();
+                let _ = write!(stdout, ">>> ");
+                stdout.flush().unwrap();
+            }
             s.clear();
-            let r = io::stdin().read_line(&mut s);
+            let r = self.vm.io().stdin().read_line(&mut s);
             s = s.replace('\n', "");
             self.history.push(s.clone());
             if let Err(e) = r {
-                println!("io error - {e}");
+                self.output(format_args!("io error - {e}"));
                 continue;
             }
             let cmd = match Self::parse_command(&s) {
                 Ok(e) => e,
                 Err(err) => {
-                    println!("{err}");
+                    self.output(err);
                     continue;
                 }
             };
 
-            if let Err(e) = self.run_command(&cmd) {
-                println!("{e}");
+            match self.run_command(&cmd) {
+                Err(ReplError::VMError(VMError::Exit(code))) => process::exit(code),
+                Err(e) => self.output(e),
+                Ok(()) => {}
             }
         }
     }
@@ -128,20 +135,20 @@ impl Repl {
         match cmd {
             ReplCommand::ShowStack(i) => {
                 if self.vm.stack.is_empty() {
-                    println!("<stack empty>");
+                    self.output("<stack empty>");
                 } else {
                     for (n, i) in self.vm.stack.iter().rev().take(*i).enumerate() {
-                        println!("{n}. {i:?}", n = n + 1);
+                        self.output(format_args!("{n}. {i:?}", n = n + 1));
                     }
                 }
             }
             ReplCommand::Exit => process::exit(0),
             ReplCommand::ShowHistory(i) => {
                 if self.history.is_empty() {
-                    println!("<history empty>");
+                    self.output("<history empty>");
                 } else {
                     for (n, i) in self.history.iter().take(*i).enumerate() {
-                        println!("{n}. {i}", n = n + 1);
+                        self.output(format_args!("{n}. {i}", n = n + 1));
                     }
                 }
             }
@@ -171,9 +178,7 @@ impl Repl {
                     .map(|_| ())
                     .map_err(ReplError::VMError);
             }
-            ReplCommand::ShowHelp => {
-                println!("{}", get_repl_help())
-            }
+            ReplCommand::ShowHelp => self.output(get_repl_help()),
         }
 
         Ok(())
@@ -0,0 +1,99 @@
+use crate::{machine::VMError, number};
+use enalang_ir as ir;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct Map {
+    entries: Vec<(ir::Value, ir::Value)>,
+    index: HashMap<u64, Vec<usize>>,
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn hash(key: &ir::Value) -> Result<u64, VMError> {
+        key.get_hash()
+            .ok_or_else(|| VMError::CannotHash(key.clone()))
+    }
+
+    fn position(&self, key: &ir::Value) -> Result<Option<usize>, VMError> {
+        let hash = Self::hash(key)?;
+
+        Ok(self.index.get(&hash).and_then(|bucket| {
+            bucket
+                .iter()
+                .find(|i| number::values_equal(&self.entries[**i].0, key))
+                .copied()
+        }))
+    }
+
+    fn reindex(&mut self) {
+        self.index = HashMap::new();
+        for (i, (key, _)) in self.entries.iter().enumerate() {
+            if let Some(hash) = key.get_hash() {
+                self.index.entry(hash).or_default().push(i);
+            }
+        }
+    }
+
+    pub fn insert(
+        &mut self,
+        key: ir::Value,
+        value: ir::Value,
+    ) -> Result<Option<ir::Value>, VMError> {
+        if let Some(i) = self.position(&key)? {
+            return Ok(Some(std::mem::replace(&mut self.entries[i].1, value)));
+        }
+
+        let hash = Self::hash(&key)?;
+        self.index.entry(hash).or_default().push(self.entries.len());
+        self.entries.push((key, value));
+
+        Ok(None)
+    }
+
+    pub fn get(&self, key: &ir::Value) -> Result<Option<&ir::Value>, VMError> {
+        Ok(self.position(key)?.map(|i| &self.entries[i].1))
+    }
+
+    pub fn remove(&mut self, key: &ir::Value) -> Result<Option<(ir::Value, ir::Value)>, VMError> {
+        match self.position(key)? {
+            Some(i) => {
+                let entry = self.entries.remove(i);
+                self.reindex();
+                Ok(Some(entry))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn contains_key(&self, key: &ir::Value) -> Result<bool, VMError> {
+        Ok(self.position(key)?.is_some())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &ir::Value> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &ir::Value> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(ir::Value, ir::Value)> {
+        self.entries.iter()
+    }
+
+    pub fn entry_at(&self, i: usize) -> Option<&(ir::Value, ir::Value)> {
+        self.entries.get(i)
+    }
+}
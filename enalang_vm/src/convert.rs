@@ -0,0 +1,198 @@
+use crate::{
+    machine::{VMError, VM},
+    number::Number,
+};
+use enalang_ir as ir;
+use flexstr::{LocalStr, ToLocalStr};
+use num_traits::ToPrimitive;
+
+// conversions between stack values and rust types, used by natives declared with `ena_native`
+
+pub trait PopValue: Sized {
+    fn pop_value(vm: &mut VM) -> Result<Self, VMError>;
+
+    // a value that keeps its reference while the native runs, see `pop_arg`
+    fn held(&self) -> Option<ir::Value> {
+        None
+    }
+}
+
+pub trait PushValue {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError>;
+}
+
+// the value is popped without releasing its reference, so an object it points to
+// can't be freed before the native is done with it
+impl PopValue for ir::Value {
+    fn pop_value(vm: &mut VM) -> Result<Self, VMError> {
+        vm.stack.pop().ok_or(VMError::StackEnded)
+    }
+
+    fn held(&self) -> Option<ir::Value> {
+        Some(self.clone())
+    }
+}
+
+impl PopValue for LocalStr {
+    fn pop_value(vm: &mut VM) -> Result<Self, VMError> {
+        vm.pop_string()
+    }
+}
+
+impl PopValue for String {
+    fn pop_value(vm: &mut VM) -> Result<Self, VMError> {
+        vm.pop_string().map(|x| x.to_string())
+    }
+}
+
+impl PopValue for Vec<u8> {
+    fn pop_value(vm: &mut VM) -> Result<Self, VMError> {
+        vm.pop_bytes()
+    }
+}
+
+impl PopValue for bool {
+    fn pop_value(vm: &mut VM) -> Result<Self, VMError> {
+        match vm.pop()? {
+            ir::Value::Boolean(b) => Ok(b),
+            _ => Err(VMError::ExpectedBoolean),
+        }
+    }
+}
+
+impl PopValue for i64 {
+    fn pop_value(vm: &mut VM) -> Result<Self, VMError> {
+        vm.pop_integer()?.to_i64().ok_or(VMError::ExpectedInteger)
+    }
+}
+
+impl PopValue for usize {
+    fn pop_value(vm: &mut VM) -> Result<Self, VMError> {
+        vm.pop_pointer()
+    }
+}
+
+impl PopValue for f64 {
+    fn pop_value(vm: &mut VM) -> Result<Self, VMError> {
+        vm.pop_number().map(|x| x.as_f64())
+    }
+}
+
+impl PopValue for ir::BigInt {
+    fn pop_value(vm: &mut VM) -> Result<Self, VMError> {
+        vm.pop_integer()
+    }
+}
+
+impl PopValue for Number {
+    fn pop_value(vm: &mut VM) -> Result<Self, VMError> {
+        vm.pop_number()
+    }
+}
+
+// runs the body of a native, then releases the references its arguments held
+pub fn run_native<F>(vm: &mut VM, f: F) -> Result<(), VMError>
+where
+    F: FnOnce(&mut VM, &mut Vec<ir::Value>) -> Result<(), VMError>,
+{
+    let mut held = Vec::new();
+    let result = f(vm, &mut held);
+    for value in held {
+        vm.handle_minus(value)?;
+    }
+    result
+}
+
+pub fn pop_arg<T: PopValue>(vm: &mut VM, held: &mut Vec<ir::Value>) -> Result<T, VMError> {
+    let value = T::pop_value(vm)?;
+    held.extend(value.held());
+    Ok(value)
+}
+
+impl PushValue for () {
+    fn push_value(self, _: &mut VM) -> Result<(), VMError> {
+        Ok(())
+    }
+}
+
+impl PushValue for ir::Value {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError> {
+        vm.push(self)
+    }
+}
+
+impl PushValue for LocalStr {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError> {
+        vm.push(ir::Value::String(self))
+    }
+}
+
+impl PushValue for String {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError> {
+        vm.push(ir::Value::String(self.to_local_str()))
+    }
+}
+
+impl PushValue for &str {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError> {
+        vm.push(ir::Value::String(self.to_local_str()))
+    }
+}
+
+impl PushValue for Vec<u8> {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError> {
+        vm.push(ir::Value::Bytes(self))
+    }
+}
+
+impl PushValue for bool {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError> {
+        vm.push(ir::Value::Boolean(self))
+    }
+}
+
+impl PushValue for i64 {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError> {
+        vm.push(ir::Value::Integer(self))
+    }
+}
+
+impl PushValue for usize {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError> {
+        vm.push(ir::Value::Integer(self as i64))
+    }
+}
+
+impl PushValue for f64 {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError> {
+        vm.push(ir::Value::Number(self))
+    }
+}
+
+impl PushValue for ir::BigInt {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError> {
+        vm.push(ir::Value::integer(self))
+    }
+}
+
+impl PushValue for Number {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError> {
+        vm.push(self.into_value())
+    }
+}
+
+// `None` is pushed as null
+impl<T: PushValue> PushValue for Option<T> {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError> {
+        match self {
+            Some(value) => value.push_value(vm),
+            None => vm.push(ir::Value::Null),
+        }
+    }
+}
+
+impl<T: PushValue> PushValue for Result<T, VMError> {
+    fn push_value(self, vm: &mut VM) -> Result<(), VMError> {
+        self?.push_value(vm)
+    }
+}
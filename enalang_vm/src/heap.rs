@@ -1,8 +1,9 @@
 use enalang_ir as ir;
+use flexstr::LocalStr;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{any::Any, collections::HashMap, fmt, rc::Rc};
 
-use super::machine;
+use super::{collections, machine, number, resources, vmio};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, thiserror::Error)]
 pub enum HeapError {
@@ -22,25 +23,101 @@ pub struct MemoryBlock {
     pub size: usize,
 }
 
+impl MemoryBlock {
+    pub fn contains(&self, pointer: usize) -> bool {
+        pointer == self.pointer || (pointer > self.pointer && pointer < self.pointer + self.size)
+    }
+}
+
+#[derive(Debug)]
+pub enum HeapObject {
+    Array(Vec<ir::Value>),
+    Map(collections::Map),
+    File(resources::FileHandle),
+    Listener(resources::Listener),
+    Socket(resources::Socket),
+    Host(HostObject),
+}
+
+// a rust value handed to ena code by a native. the name is shown by `into_string`,
+// the value is only reachable from rust by downcasting it to its original type
+pub struct HostObject {
+    pub type_name: LocalStr,
+    pub value: Box<dyn Any>,
+}
+
+impl HostObject {
+    pub fn new<T: Any>(type_name: &str, value: T) -> Self {
+        Self {
+            type_name: type_name.into(),
+            value: Box::new(value),
+        }
+    }
+
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.value.downcast_ref()
+    }
+
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.value.downcast_mut()
+    }
+}
+
+impl fmt::Debug for HostObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "HostObject({})", self.type_name)
+    }
+}
+
+impl HeapObject {
+    pub fn values(&self) -> Vec<ir::Value> {
+        match self {
+            HeapObject::Array(vec) => vec.clone(),
+            HeapObject::Map(map) => map
+                .iter()
+                .flat_map(|(k, v)| [k.clone(), v.clone()])
+                .collect(),
+            HeapObject::File(_)
+            | HeapObject::Listener(_)
+            | HeapObject::Socket(_)
+            | HeapObject::Host(_) => vec![],
+        }
+    }
+}
+
 pub struct Heap {
     pub gc_enabled: bool,
     pub debug_gc: bool,
+    pub io: Rc<dyn vmio::VMIO>,
     heap: HashMap<usize, ir::Value>,
     blocks: Vec<MemoryBlock>,
     rc: HashMap<usize, usize>,
+    objects: HashMap<usize, HeapObject>,
+    weak: HashMap<usize, usize>,
+    weak_ids: HashMap<usize, usize>,
+    next_weak: usize,
 }
 
 impl Heap {
-    pub fn new(gc_enabled: bool, debug_gc: bool) -> Self {
+    pub fn new(gc_enabled: bool, debug_gc: bool, io: Rc<dyn vmio::VMIO>) -> Self {
         Self {
             heap: HashMap::new(),
             rc: HashMap::new(),
             blocks: vec![],
+            objects: HashMap::new(),
+            weak: HashMap::new(),
+            weak_ids: HashMap::new(),
+            next_weak: 0,
             gc_enabled,
             debug_gc,
+            io,
         }
     }
 
+    fn debug(&self, message: fmt::Arguments) {
+        let _ = writeln!(self.io.diagnostics(), "GC_DEBUG: {message}");
+    }
+
     fn find_space(&self, size: usize) -> usize {
         let mut pointer = 0;
 
@@ -65,9 +142,7 @@ impl Heap {
 
     fn get_pointer_owner_block(&self, pointer: usize) -> Option<MemoryBlock> {
         for block in &self.blocks {
-            if pointer == block.pointer
-                || (pointer > block.pointer && pointer < block.pointer + block.size)
-            {
+            if block.contains(pointer) {
                 return Some(*block);
             }
         }
@@ -112,9 +187,13 @@ impl Heap {
     fn clear_memory(&mut self, pointer: usize, size: usize) -> Result<(), HeapError> {
         for i in 0..size {
             if self.debug_gc && self.heap.contains_key(&(pointer + i)) {
-                println!("GC_DEBUG: freeing pointer {}", pointer + i);
+                self.debug(format_args!("freeing pointer {}", pointer + i));
             }
-            if let Some(ir::Value::Pointer(i)) = self.heap.remove(&(pointer + i)) {
+            if let Some(i) = self
+                .heap
+                .remove(&(pointer + i))
+                .and_then(|x| x.as_heap_pointer())
+            {
                 self.rc_minus(i)?;
             }
         }
@@ -126,6 +205,27 @@ impl Heap {
         self.blocks.retain(|x| x.pointer != pointer);
     }
 
+    fn invalidate_weak(&mut self, block: MemoryBlock) {
+        self.weak.retain(|_, pointer| !block.contains(*pointer));
+        self.weak_ids.retain(|pointer, _| !block.contains(*pointer));
+    }
+
+    fn move_weak(&mut self, src: MemoryBlock, dest: usize) {
+        let moved: Vec<(usize, usize)> = self
+            .weak_ids
+            .iter()
+            .filter(|(pointer, _)| src.contains(**pointer))
+            .map(|(pointer, id)| (*pointer, *id))
+            .collect();
+
+        for (pointer, id) in moved {
+            let new_pointer = dest + (pointer - src.pointer);
+            self.weak_ids.remove(&pointer);
+            self.weak_ids.insert(new_pointer, id);
+            self.weak.insert(id, new_pointer);
+        }
+    }
+
     fn rc_check(&mut self, pointer: usize) -> Result<(), HeapError> {
         if !self.gc_enabled {
             return Ok(());
@@ -141,10 +241,10 @@ impl Heap {
         if let Some(i) = &self.rc.get(&block.pointer) {
             if **i == 0 {
                 if self.debug_gc {
-                    println!(
-                        "GC_DEBUG: freeing {} with size {}",
+                    self.debug(format_args!(
+                        "freeing {} with size {}",
                         block.pointer, block.size
-                    );
+                    ));
                 }
                 self.free(block.pointer)
             } else {
@@ -173,14 +273,14 @@ impl Heap {
         self.rc.insert(block.pointer, new_value);
 
         if self.debug_gc {
-            println!(
-                "GC_DEBUG: {}(in block {}, value: {value:?}) - RC:{}->{}",
+            self.debug(format_args!(
+                "{}(in block {}, value: {value:?}) - RC:{}->{}",
                 pointer,
                 block.pointer,
                 current_rc,
                 new_value,
                 value = self.heap.get(&pointer),
-            );
+            ));
         }
 
         self.rc_check(block.pointer)
@@ -203,18 +303,22 @@ impl Heap {
 
     pub fn get(&self, pointer: usize) -> Option<ir::Value> {
         if self.gc_enabled && !self.is_used(pointer) {
-            println!("GC_DEBUG: read from an unallocated area at pointer {pointer}",);
+            self.debug(format_args!(
+                "read from an unallocated area at pointer {pointer}"
+            ));
         }
         self.heap.get(&pointer).cloned()
     }
 
     pub fn set(&mut self, pointer: usize, value: ir::Value) -> Result<(), HeapError> {
         if self.gc_enabled && !self.is_used(pointer) {
-            println!("GC_DEBUG: write to an unallocated area at pointer {pointer}",);
+            self.debug(format_args!(
+                "write to an unallocated area at pointer {pointer}"
+            ));
         }
         self.heap.insert(pointer, value.clone());
 
-        if let ir::Value::Pointer(val) = value {
+        if let Some(val) = value.as_heap_pointer() {
             self.rc_plus(val)?;
         }
         Ok(())
@@ -237,9 +341,10 @@ impl Heap {
 
         if new_ptr != pointer {
             if self.debug_gc {
-                println!("GC_DEBUG: reallocated {pointer}->{new_ptr}");
+                self.debug(format_args!("reallocated {pointer}->{new_ptr}"));
             }
             self.move_memory(pointer, new_ptr, block.size);
+            self.move_weak(block, new_ptr);
             self.rc_reset(block.pointer);
             self.remove_block(pointer);
         }
@@ -250,10 +355,10 @@ impl Heap {
         let block = self.create_block(size);
 
         if self.debug_gc {
-            println!(
-                "GC_DEBUG: allocating {block_pointer} with size {size}",
+            self.debug(format_args!(
+                "allocating {block_pointer} with size {size}",
                 block_pointer = block.pointer
-            );
+            ));
         }
         self.rc_plus(block.pointer)?;
 
@@ -268,8 +373,106 @@ impl Heap {
             }
         };
 
+        self.invalidate_weak(block);
         self.clear_memory(block.pointer, block.size)?;
         self.remove_block(block.pointer);
+
+        if let Some(object) = self.objects.remove(&block.pointer) {
+            for value in object.values() {
+                if let Some(i) = value.as_heap_pointer() {
+                    self.rc_minus(i)?;
+                }
+            }
+        }
         Ok(())
     }
+
+    // values moved into the object keep the references they already hold
+    pub fn alloc_object(&mut self, object: HeapObject) -> Result<usize, HeapError> {
+        let block = self.alloc(1)?;
+        self.objects.insert(block.pointer, object);
+
+        Ok(block.pointer)
+    }
+
+    pub fn get_object(&self, pointer: usize) -> Option<&HeapObject> {
+        self.objects.get(&pointer)
+    }
+
+    pub fn get_object_mut(&mut self, pointer: usize) -> Option<&mut HeapObject> {
+        self.objects.get_mut(&pointer)
+    }
+
+    pub fn pointer_value(&self, pointer: usize) -> ir::Value {
+        match self.objects.get(&pointer) {
+            Some(HeapObject::Array(_)) => ir::Value::Array(pointer),
+            Some(HeapObject::Map(_)) => ir::Value::Map(pointer),
+            Some(HeapObject::File(_) | HeapObject::Listener(_) | HeapObject::Socket(_)) => {
+                ir::Value::Handle(pointer)
+            }
+            Some(HeapObject::Host(_)) => ir::Value::Host(pointer),
+            None => ir::Value::Pointer(pointer),
+        }
+    }
+
+    pub fn values_equal(&self, a: &ir::Value, b: &ir::Value) -> bool {
+        self.values_equal_inner(a, b, &mut Vec::new())
+    }
+
+    fn values_equal_inner(
+        &self,
+        a: &ir::Value,
+        b: &ir::Value,
+        seen: &mut Vec<(usize, usize)>,
+    ) -> bool {
+        let (a_ptr, b_ptr) = match (a, b) {
+            (ir::Value::Array(x), ir::Value::Array(y)) | (ir::Value::Map(x), ir::Value::Map(y)) => {
+                (*x, *y)
+            }
+            _ => return number::values_equal(a, b),
+        };
+
+        if a_ptr == b_ptr || seen.contains(&(a_ptr, b_ptr)) {
+            return true;
+        }
+        seen.push((a_ptr, b_ptr));
+
+        match (self.get_object(a_ptr), self.get_object(b_ptr)) {
+            (Some(HeapObject::Array(x)), Some(HeapObject::Array(y))) => {
+                x.len() == y.len()
+                    && x.iter()
+                        .zip(y.iter())
+                        .all(|(x, y)| self.values_equal_inner(x, y, seen))
+            }
+            (Some(HeapObject::Map(x)), Some(HeapObject::Map(y))) => {
+                x.len() == y.len()
+                    && x.iter().all(|(key, x)| match y.get(key) {
+                        Ok(Some(y)) => self.values_equal_inner(x, y, seen),
+                        _ => false,
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    pub fn downgrade(&mut self, pointer: usize) -> Result<usize, HeapError> {
+        if !self.is_used(pointer) {
+            return Err(HeapError::BlockNotAllocated(pointer));
+        }
+
+        if let Some(id) = self.weak_ids.get(&pointer) {
+            return Ok(*id);
+        }
+
+        let id = self.next_weak;
+        self.next_weak += 1;
+        self.weak.insert(id, pointer);
+        self.weak_ids.insert(pointer, id);
+
+        Ok(id)
+    }
+
+    pub fn upgrade(&self, weak: usize) -> Option<usize> {
+        self.weak.get(&weak).copied()
+    }
 }
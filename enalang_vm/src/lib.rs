@@ -1,4 +1,14 @@
+// lets the code generated by `ena_native` refer to this crate from inside it
+extern crate self as enalang_vm;
+
+pub use enalang_ir;
+
 pub mod blocks;
+pub mod collections;
+pub mod convert;
 pub mod heap;
 pub mod machine;
 pub mod native;
+pub mod number;
+pub mod resources;
+pub mod vmio;
@@ -1,11 +1,12 @@
 use crate::{
     blocks::{self, BlocksError},
-    heap, native,
+    heap, native, number, vmio,
 };
 use enalang_ir as ir;
 use flexstr::{local_str, LocalStr};
+use rand::{rngs::StdRng, SeedableRng};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc, time::Instant};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, thiserror::Error)]
 pub enum VMError {
@@ -27,8 +28,28 @@ pub enum VMError {
     ExpectedInteger,
     #[error("expected block")]
     ExpectedBlock,
+    #[error("expected bytes")]
+    ExpectedBytes,
     #[error("expected pointer")]
     ExpectedPointer,
+    #[error("expected weak pointer")]
+    ExpectedWeakPointer,
+    #[error("expected array")]
+    ExpectedArray,
+    #[error("expected map")]
+    ExpectedMap,
+    #[error("expected file handle")]
+    ExpectedFile,
+    #[error("expected socket handle")]
+    ExpectedSocket,
+    #[error("expected listener handle")]
+    ExpectedListener,
+    #[error("expected host object of type `{0}`")]
+    ExpectedHost(LocalStr),
+    #[error("index {0} is out of bounds")]
+    IndexOutOfBounds(usize),
+    #[error("cannot hash {0:?}")]
+    CannotHash(ir::Value),
     #[error("expected value")]
     ExpectedValue,
     #[error("expected exception")]
@@ -39,6 +60,14 @@ pub enum VMError {
     CannotCompare(ir::Value, ir::Value),
     #[error("cannot convert {0:?}")]
     CannotConvert(ir::Value),
+    #[error("division by zero")]
+    DivisionByZero,
+    #[error("invalid utf-8")]
+    InvalidUtf8,
+    #[error("cannot decode - `{0}`")]
+    CannotDecode(LocalStr),
+    #[error("invalid regex - `{0}`")]
+    InvalidRegex(LocalStr),
     #[error("heap error - `{0}`")]
     HeapError(heap::HeapError),
     #[error("bad pointer - `{0}`")]
@@ -51,6 +80,14 @@ pub enum VMError {
     NoSingleEval,
     #[error("blocks error - {0}")]
     Blocks(BlocksError),
+    #[error("exit with code {0}")]
+    Exit(i32),
+    #[error("running subprocesses is not allowed")]
+    SubprocessNotAllowed,
+    #[error("empty range")]
+    EmptyRange,
+    #[error("native is already running")]
+    NativeAlreadyRunning,
 }
 
 #[derive(Clone, Debug)]
@@ -263,6 +300,8 @@ pub struct VMOptions {
     pub enable_gc: bool,
     pub debug_gc: bool,
     pub debug_calls: bool,
+    pub allow_subprocess: bool,
+    pub seed: Option<u64>,
 }
 
 impl VMOptions {
@@ -277,6 +316,11 @@ pub struct VM {
     pub heap: heap::Heap,
     pub options: VMOptions,
     pub scope_manager: ScopeManager,
+    pub regex_cache: HashMap<LocalStr, regex::Regex>,
+    pub(crate) io: Rc<dyn vmio::VMIO>,
+    pub args: Vec<LocalStr>,
+    pub start_time: Instant,
+    pub rng: StdRng,
 }
 
 impl Default for VMOptions {
@@ -286,24 +330,49 @@ impl Default for VMOptions {
             enable_gc: true,
             debug_gc: false,
             debug_calls: false,
+            allow_subprocess: true,
+            seed: None,
         }
     }
 }
 
 impl VM {
     pub fn new(options: VMOptions) -> Self {
+        let io: Rc<dyn vmio::VMIO> = Rc::new(vmio::ProcessIO::new());
         Self {
             call_stack: Vec::new(),
-            heap: heap::Heap::new(options.enable_gc, options.debug_gc),
+            heap: heap::Heap::new(options.enable_gc, options.debug_gc, io.clone()),
             options,
             stack: Vec::new(),
             scope_manager: ScopeManager::new(),
+            regex_cache: HashMap::new(),
+            io,
+            args: Vec::new(),
+            start_time: Instant::now(),
+            rng: match options.seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            },
         }
     }
 
+    // replaces the process streams, e.g. with a `vmio::MemoryIO` when embedding the vm
+    pub fn set_io(&mut self, io: Rc<dyn vmio::VMIO>) {
+        self.heap.io = io.clone();
+        self.io = io;
+    }
+
+    pub fn io(&self) -> &dyn vmio::VMIO {
+        self.io.as_ref()
+    }
+
     pub fn clean(&mut self) {
         self.call_stack = Vec::new();
-        self.heap = heap::Heap::new(self.options.enable_gc, self.options.debug_gc);
+        self.heap = heap::Heap::new(
+            self.options.enable_gc,
+            self.options.debug_gc,
+            self.io.clone(),
+        );
         self.stack = Vec::new();
         self.scope_manager = ScopeManager::new();
     }
@@ -328,7 +397,7 @@ impl VM {
     }
 
     pub fn handle_plus(&mut self, value: ir::Value) -> Result<(), VMError> {
-        if let ir::Value::Pointer(ptr) = value {
+        if let Some(ptr) = value.as_heap_pointer() {
             self.heap.rc_plus(ptr).map_err(VMError::HeapError)
         } else {
             Ok(())
@@ -336,25 +405,58 @@ impl VM {
     }
 
     pub fn handle_minus(&mut self, value: ir::Value) -> Result<(), VMError> {
-        if let ir::Value::Pointer(ptr) = value {
+        if let Some(ptr) = value.as_heap_pointer() {
             self.heap.rc_minus(ptr).map_err(VMError::HeapError)
         } else {
             Ok(())
         }
     }
 
+    pub fn pop_integer(&mut self) -> Result<ir::BigInt, VMError> {
+        number::Number::from_value(&self.pop()?)
+            .and_then(|x| x.as_integer())
+            .ok_or(VMError::ExpectedInteger)
+    }
+
     pub fn pop_pointer(&mut self) -> Result<usize, VMError> {
         let val = self.pop()?;
-        match val {
-            ir::Value::Number(i) => {
-                let in_usize = i as usize;
-                if (in_usize as f64) != i {
-                    return Err(VMError::ExpectedInteger);
-                }
+        if let ir::Value::Integer(i) = val {
+            return usize::try_from(i).map_err(|_| VMError::ExpectedInteger);
+        }
 
-                Ok(in_usize)
-            }
-            _ => Err(VMError::ExpectedPointer),
+        match number::Number::from_value(&val) {
+            Some(num) => num
+                .as_integer()
+                .and_then(|x| usize::try_from(&x).ok())
+                .ok_or(VMError::ExpectedInteger),
+            None => Err(VMError::ExpectedPointer),
+        }
+    }
+
+    pub fn pop_number(&mut self) -> Result<number::Number, VMError> {
+        number::Number::from_value(&self.pop()?).ok_or(VMError::ExpectedNumber)
+    }
+
+    pub fn pop_string(&mut self) -> Result<LocalStr, VMError> {
+        match self.pop()? {
+            ir::Value::String(st) => Ok(st),
+            _ => Err(VMError::ExpectedString),
+        }
+    }
+
+    pub fn pop_bytes(&mut self) -> Result<Vec<u8>, VMError> {
+        match self.pop()? {
+            ir::Value::Bytes(bytes) => Ok(bytes),
+            _ => Err(VMError::ExpectedBytes),
+        }
+    }
+
+    // strings are taken as their utf-8 bytes
+    pub fn pop_contents(&mut self) -> Result<Vec<u8>, VMError> {
+        match self.pop()? {
+            ir::Value::String(st) => Ok(st.as_bytes().to_vec()),
+            ir::Value::Bytes(bytes) => Ok(bytes),
+            _ => Err(VMError::ExpectedString),
         }
     }
 
@@ -393,11 +495,15 @@ impl VM {
 
     pub fn run_block(&mut self, block_name: &LocalStr) -> Result<bool, VMError> {
         if self.options.debug_calls {
-            println!("CALL_DEBUG: {block_name}");
+            let _ = writeln!(self.io.diagnostics(), "CALL_DEBUG: {block_name}");
         }
 
         if self.options.debug_stack {
-            println!("STACK_DEBUG: {stack:?}", stack = self.stack);
+            let _ = writeln!(
+                self.io.diagnostics(),
+                "STACK_DEBUG: {stack:?}",
+                stack = self.stack
+            );
         }
 
         let block = match self.scope_manager.blocks().get_block(block_name).cloned() {
@@ -426,7 +532,7 @@ impl VM {
 
         let v: Result<bool, VMError> = match block {
             blocks::VMBlock::NativeHandler(f) => {
-                f(native::NativeHandlerCtx { vm: self }).map(|_| false)
+                f.call(native::NativeHandlerCtx { vm: self }).map(|_| false)
             }
             blocks::VMBlock::IR(block) => {
                 let typ = block.run_type;
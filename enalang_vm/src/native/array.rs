@@ -0,0 +1,179 @@
+use crate::{
+    define_native_group,
+    heap::{self, HeapObject},
+    machine, native,
+};
+use enalang_ir as ir;
+
+fn array(vm: &mut machine::VM, pointer: usize) -> Result<&mut Vec<ir::Value>, machine::VMError> {
+    match vm.heap.get_object_mut(pointer) {
+        Some(HeapObject::Array(vec)) => Ok(vec),
+        _ => Err(machine::VMError::BadPointer(pointer)),
+    }
+}
+
+fn with_array<F>(ctx: native::NativeHandlerCtx, f: F) -> Result<(), machine::VMError>
+where
+    F: FnOnce(&mut machine::VM, usize) -> Result<(), machine::VMError>,
+{
+    let pointer = match ctx.vm.stack.pop() {
+        Some(ir::Value::Array(pointer)) => pointer,
+        Some(other) => {
+            ctx.vm.handle_minus(other)?;
+            return Err(machine::VMError::ExpectedArray);
+        }
+        None => return Err(machine::VMError::StackEnded),
+    };
+
+    let result = f(ctx.vm, pointer);
+    ctx.vm.handle_minus(ir::Value::Array(pointer))?;
+    result
+}
+
+pub fn new(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let pointer = heap::heap_result_into_vm(ctx.vm.heap.alloc_object(HeapObject::Array(vec![])))?;
+    ctx.vm.stack.push(ir::Value::Array(pointer));
+    Ok(())
+}
+
+pub fn from_stack(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let len = ctx.vm.pop_pointer()?;
+    if len > ctx.vm.stack.len() {
+        return Err(machine::VMError::StackEnded);
+    }
+
+    let values = ctx.vm.stack.split_off(ctx.vm.stack.len() - len);
+    let pointer = heap::heap_result_into_vm(ctx.vm.heap.alloc_object(HeapObject::Array(values)))?;
+    ctx.vm.stack.push(ir::Value::Array(pointer));
+    Ok(())
+}
+
+pub fn push(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_array(ctx, |vm, pointer| {
+        let value = vm.stack.pop().ok_or(machine::VMError::StackEnded)?;
+        array(vm, pointer)?.push(value);
+        Ok(())
+    })
+}
+
+pub fn pop(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_array(ctx, |vm, pointer| {
+        let value = array(vm, pointer)?.pop().unwrap_or(ir::Value::Null);
+        vm.stack.push(value);
+        Ok(())
+    })
+}
+
+pub fn get(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_array(ctx, |vm, pointer| {
+        let index = vm.pop_pointer()?;
+        let value = array(vm, pointer)?
+            .get(index)
+            .cloned()
+            .unwrap_or(ir::Value::Null);
+        vm.push(value)
+    })
+}
+
+pub fn set(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_array(ctx, |vm, pointer| {
+        let index = vm.pop_pointer()?;
+        let value = vm.stack.pop().ok_or(machine::VMError::StackEnded)?;
+        let vec = array(vm, pointer)?;
+
+        match vec.get_mut(index) {
+            Some(el) => {
+                let old = std::mem::replace(el, value);
+                vm.handle_minus(old)
+            }
+            None => {
+                vm.handle_minus(value)?;
+                Err(machine::VMError::IndexOutOfBounds(index))
+            }
+        }
+    })
+}
+
+pub fn insert(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_array(ctx, |vm, pointer| {
+        let index = vm.pop_pointer()?;
+        let value = vm.stack.pop().ok_or(machine::VMError::StackEnded)?;
+        let vec = array(vm, pointer)?;
+
+        if index > vec.len() {
+            vm.handle_minus(value)?;
+            return Err(machine::VMError::IndexOutOfBounds(index));
+        }
+
+        vec.insert(index, value);
+        Ok(())
+    })
+}
+
+pub fn remove(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_array(ctx, |vm, pointer| {
+        let index = vm.pop_pointer()?;
+        let vec = array(vm, pointer)?;
+
+        if index >= vec.len() {
+            return Err(machine::VMError::IndexOutOfBounds(index));
+        }
+
+        let value = vec.remove(index);
+        vm.stack.push(value);
+        Ok(())
+    })
+}
+
+pub fn len(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_array(ctx, |vm, pointer| {
+        let len = array(vm, pointer)?.len();
+        vm.push(ir::Value::Integer(len as i64))
+    })
+}
+
+pub fn each(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_array(ctx, |vm, pointer| {
+        let block = match vm.pop()? {
+            ir::Value::Block(block) => block,
+            _ => return Err(machine::VMError::ExpectedBlock),
+        };
+
+        let mut i = 0;
+        while let Some(value) = array(vm, pointer)?.get(i).cloned() {
+            vm.push(value)?;
+            vm.run_block(&block)?;
+            i += 1;
+        }
+
+        Ok(())
+    })
+}
+
+pub fn unwrap(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_array(ctx, |vm, pointer| {
+        let values = array(vm, pointer)?.clone();
+
+        for value in &values {
+            vm.push(value.clone())?;
+        }
+
+        vm.push(ir::Value::Integer(values.len() as i64))
+    })
+}
+
+define_native_group! {
+    group,
+    "array",
+    "new" => new,
+    "from_stack" => from_stack,
+    "push" => push,
+    "pop" => pop,
+    "get" => get,
+    "set" => set,
+    "insert" => insert,
+    "remove" => remove,
+    "len" => len,
+    "each" => each,
+    "unwrap" => unwrap
+}
@@ -0,0 +1,103 @@
+use crate::{define_native_group, machine, native};
+use base64::Engine;
+use enalang_ir as ir;
+use flexstr::{local_fmt, ToLocalStr};
+
+pub fn len(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let bytes = ctx.vm.pop_bytes()?;
+    ctx.vm.push(ir::Value::Integer(bytes.len() as i64))
+}
+
+pub fn concat(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let mut a = ctx.vm.pop_bytes()?;
+    let b = ctx.vm.pop_bytes()?;
+    a.extend_from_slice(&b);
+    ctx.vm.push(ir::Value::Bytes(a))
+}
+
+pub fn get(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let bytes = ctx.vm.pop_bytes()?;
+    let index = ctx.vm.pop_pointer()?;
+
+    match bytes.get(index) {
+        Some(byte) => ctx.vm.push(ir::Value::Integer(*byte as i64)),
+        None => ctx.vm.push(ir::Value::Null),
+    }
+}
+
+pub fn slice(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let bytes = ctx.vm.pop_bytes()?;
+    let start = ctx.vm.pop_pointer()?;
+    let end = ctx.vm.pop_pointer()?;
+
+    if end > bytes.len() {
+        return Err(machine::VMError::IndexOutOfBounds(end));
+    }
+    if start > end {
+        return Err(machine::VMError::IndexOutOfBounds(start));
+    }
+
+    ctx.vm.push(ir::Value::Bytes(bytes[start..end].to_vec()))
+}
+
+pub fn from_string(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    if let ir::Value::String(st) = ctx.vm.pop()? {
+        ctx.vm.push(ir::Value::Bytes(st.as_bytes().to_vec()))
+    } else {
+        Err(machine::VMError::ExpectedString)
+    }
+}
+
+pub fn into_string(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let bytes = ctx.vm.pop_bytes()?;
+    let st = std::str::from_utf8(&bytes).map_err(|_| machine::VMError::InvalidUtf8)?;
+    ctx.vm.push(ir::Value::String(st.to_local_str()))
+}
+
+pub fn to_hex(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let bytes = ctx.vm.pop_contents()?;
+    ctx.vm
+        .push(ir::Value::String(hex::encode(bytes).to_local_str()))
+}
+
+pub fn from_hex(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    if let ir::Value::String(st) = ctx.vm.pop()? {
+        let bytes = hex::decode(st.as_str())
+            .map_err(|e| machine::VMError::CannotDecode(local_fmt!("{e}")))?;
+        ctx.vm.push(ir::Value::Bytes(bytes))
+    } else {
+        Err(machine::VMError::ExpectedString)
+    }
+}
+
+pub fn to_base64(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let bytes = ctx.vm.pop_contents()?;
+    let st = base64::engine::general_purpose::STANDARD.encode(bytes);
+    ctx.vm.push(ir::Value::String(st.to_local_str()))
+}
+
+pub fn from_base64(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    if let ir::Value::String(st) = ctx.vm.pop()? {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(st.as_str())
+            .map_err(|e| machine::VMError::CannotDecode(local_fmt!("{e}")))?;
+        ctx.vm.push(ir::Value::Bytes(bytes))
+    } else {
+        Err(machine::VMError::ExpectedString)
+    }
+}
+
+define_native_group! {
+    group,
+    "bytes",
+    "len" => len,
+    "concat" => concat,
+    "get" => get,
+    "slice" => slice,
+    "from_string" => from_string,
+    "into_string" => into_string,
+    "to_hex" => to_hex,
+    "from_hex" => from_hex,
+    "to_base64" => to_base64,
+    "from_base64" => from_base64
+}
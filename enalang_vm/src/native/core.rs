@@ -1,6 +1,11 @@
-use crate::{define_native_group, heap, machine, native};
+use crate::{
+    define_native_group, heap, machine, native,
+    number::{self, Number},
+};
 use enalang_ir as ir;
 use flexstr::local_fmt;
+use num_traits::Zero;
+use std::cmp::Ordering;
 
 pub fn hash(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     let val = ctx.vm.pop()?;
@@ -55,16 +60,26 @@ pub fn swap(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
 
 fn shape_ptr_num_pair(one: ir::Value, two: ir::Value) -> Result<(usize, usize), machine::VMError> {
     match (one, two) {
-        (ir::Value::Pointer(a), ir::Value::Number(b)) => Ok((a, b as usize)),
-        (ir::Value::Number(a), ir::Value::Pointer(b)) => Ok((a as usize, b)),
+        (ir::Value::Pointer(a), b) => match Number::from_value(&b) {
+            Some(b) => Ok((a, b.as_f64() as usize)),
+            None => Err(machine::VMError::ExpectedNumber),
+        },
+        (a, ir::Value::Pointer(b)) => match Number::from_value(&a) {
+            Some(a) => Ok((a.as_f64() as usize, b)),
+            None => Err(machine::VMError::ExpectedNumber),
+        },
         _ => Err(machine::VMError::ExpectedNumber),
     }
 }
 
+fn pop_numbers(vm: &mut machine::VM) -> Result<(Number, Number), machine::VMError> {
+    number::pair(&vm.pop()?, &vm.pop()?).ok_or(machine::VMError::ExpectedNumber)
+}
+
 pub fn plus(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     let popped = (ctx.vm.pop()?, ctx.vm.pop()?);
-    if let (ir::Value::Number(a), ir::Value::Number(b)) = popped {
-        ctx.vm.push(ir::Value::Number(a + b))?;
+    if let Some((a, b)) = number::pair(&popped.0, &popped.1) {
+        ctx.vm.push(number::add(&a, &b).into_value())?;
     } else if let Ok((a, b)) = shape_ptr_num_pair(popped.0, popped.1) {
         ctx.vm.push(ir::Value::Pointer(a + b))?;
     } else {
@@ -75,29 +90,19 @@ pub fn plus(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
 }
 
 pub fn mul(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    if let (ir::Value::Number(a), ir::Value::Number(b)) = (ctx.vm.pop()?, ctx.vm.pop()?) {
-        ctx.vm.push(ir::Value::Number(a * b))?;
-    } else {
-        return Err(machine::VMError::ExpectedNumber);
-    }
-
-    Ok(())
+    let (a, b) = pop_numbers(ctx.vm)?;
+    ctx.vm.push(number::mul(&a, &b).into_value())
 }
 
 pub fn div(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    if let (ir::Value::Number(a), ir::Value::Number(b)) = (ctx.vm.pop()?, ctx.vm.pop()?) {
-        ctx.vm.push(ir::Value::Number(a / b))?;
-    } else {
-        return Err(machine::VMError::ExpectedNumber);
-    }
-
-    Ok(())
+    let (a, b) = pop_numbers(ctx.vm)?;
+    ctx.vm.push(number::div(&a, &b).into_value())
 }
 
 pub fn subst(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     let popped = (ctx.vm.pop()?, ctx.vm.pop()?);
-    if let (ir::Value::Number(a), ir::Value::Number(b)) = popped {
-        ctx.vm.push(ir::Value::Number(a - b))?;
+    if let Some((a, b)) = number::pair(&popped.0, &popped.1) {
+        ctx.vm.push(number::sub(&a, &b).into_value())?;
     } else if let Ok((a, b)) = shape_ptr_num_pair(popped.0, popped.1) {
         ctx.vm.push(ir::Value::Pointer(a - b))?;
     } else {
@@ -108,23 +113,78 @@ pub fn subst(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
 }
 
 pub fn pow(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    if let (ir::Value::Number(a), ir::Value::Number(b)) = (ctx.vm.pop()?, ctx.vm.pop()?) {
-        ctx.vm.push(ir::Value::Number(a.powf(b)))?;
-    } else {
-        return Err(machine::VMError::ExpectedNumber);
+    let (a, b) = pop_numbers(ctx.vm)?;
+    ctx.vm.push(number::pow(&a, &b)?.into_value())
+}
+
+pub fn root(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let (a, b) = pop_numbers(ctx.vm)?;
+    ctx.vm
+        .push(ir::Value::Number(a.as_f64().powf(1.0 / b.as_f64())))
+}
+
+fn bitwise(
+    vm: &mut machine::VM,
+    f: fn(ir::BigInt, ir::BigInt) -> ir::BigInt,
+) -> Result<(), machine::VMError> {
+    let a = vm.pop_integer()?;
+    let b = vm.pop_integer()?;
+    vm.push(ir::Value::integer(f(a, b)))
+}
+
+pub fn bit_and(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    bitwise(ctx.vm, |a, b| a & b)
+}
+
+pub fn bit_or(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    bitwise(ctx.vm, |a, b| a | b)
+}
+
+pub fn bit_xor(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    bitwise(ctx.vm, |a, b| a ^ b)
+}
+
+pub fn bit_not(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let a = ctx.vm.pop_integer()?;
+    ctx.vm.push(ir::Value::integer(!a))
+}
+
+pub fn shift_left(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let a = ctx.vm.pop_integer()?;
+    let shift = ctx.vm.pop_pointer()?;
+    if a.is_zero() {
+        return ctx.vm.push(ir::Value::integer(a));
+    }
+    if a.bits().saturating_add(shift as u64) > number::MAX_INTEGER_BITS {
+        return Err(number::too_large());
     }
+    ctx.vm.push(ir::Value::integer(a << shift))
+}
 
-    Ok(())
+pub fn shift_right(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let a = ctx.vm.pop_integer()?;
+    let shift = ctx.vm.pop_pointer()?;
+    ctx.vm.push(ir::Value::integer(a >> shift))
 }
 
-pub fn root(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    if let (ir::Value::Number(a), ir::Value::Number(b)) = (ctx.vm.pop()?, ctx.vm.pop()?) {
-        ctx.vm.push(ir::Value::Number(a.powf(1.0 / b)))?;
-    } else {
-        return Err(machine::VMError::ExpectedNumber);
+// integer division and remainder are euclidean, so the remainder is never negative
+pub fn int_div(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let a = ctx.vm.pop_integer()?;
+    let b = ctx.vm.pop_integer()?;
+    if b.is_zero() {
+        return Err(machine::VMError::DivisionByZero);
     }
+    ctx.vm.push(ir::Value::integer(number::div_euclid(&a, &b)))
+}
 
-    Ok(())
+pub fn int_rem(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let a = ctx.vm.pop_integer()?;
+    let b = ctx.vm.pop_integer()?;
+    if b.is_zero() {
+        return Err(machine::VMError::DivisionByZero);
+    }
+    ctx.vm
+        .push(number::rem(&Number::big(a), &Number::big(b))?.into_value())
 }
 
 pub fn dup(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
@@ -143,9 +203,20 @@ pub fn dup(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
 }
 
 pub fn equal(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    let (a, b) = (ctx.vm.pop()?, ctx.vm.pop()?);
+    let len = ctx.vm.stack.len();
+    if len < 2 {
+        return Err(machine::VMError::StackEnded);
+    }
+
+    // compare before popping, so that collections only referenced by the stack are still alive
+    let equal = ctx
+        .vm
+        .heap
+        .values_equal(&ctx.vm.stack[len - 1], &ctx.vm.stack[len - 2]);
+    ctx.vm.pop()?;
+    ctx.vm.pop()?;
 
-    ctx.vm.push(ir::Value::Boolean(a == b))
+    ctx.vm.push(ir::Value::Boolean(equal))
 }
 
 pub fn block_exists(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
@@ -245,6 +316,42 @@ pub fn deref(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     Ok(())
 }
 
+pub fn downgrade(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let pointer = match ctx.vm.stack.last() {
+        Some(value) => value.as_heap_pointer(),
+        None => return Err(machine::VMError::StackEnded),
+    };
+
+    if let Some(pointer) = pointer {
+        let weak = heap::heap_result_into_vm(ctx.vm.heap.downgrade(pointer))?;
+        ctx.vm.pop()?;
+        ctx.vm.push(ir::Value::WeakPointer(weak))
+    } else {
+        ctx.vm.pop()?;
+        Err(machine::VMError::ExpectedPointer)
+    }
+}
+
+pub fn upgrade(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    if let ir::Value::WeakPointer(weak) = ctx.vm.pop()? {
+        match ctx.vm.heap.upgrade(weak) {
+            Some(pointer) => ctx.vm.push(ctx.vm.heap.pointer_value(pointer)),
+            None => ctx.vm.push(ir::Value::Null),
+        }
+    } else {
+        Err(machine::VMError::ExpectedWeakPointer)
+    }
+}
+
+pub fn weak_alive(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    if let ir::Value::WeakPointer(weak) = ctx.vm.pop()? {
+        let alive = ctx.vm.heap.upgrade(weak).is_some();
+        ctx.vm.push(ir::Value::Boolean(alive))
+    } else {
+        Err(machine::VMError::ExpectedWeakPointer)
+    }
+}
+
 pub fn call(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     if let ir::Value::Block(name) = ctx.vm.pop()? {
         ctx.vm.run_block(&name)?;
@@ -278,36 +385,26 @@ pub fn and(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     }
 }
 
+fn compare(vm: &mut machine::VM, f: fn(Ordering) -> bool) -> Result<(), machine::VMError> {
+    let (a, b) = pop_numbers(vm)?;
+    let res = number::compare(&a, &b).map(f).unwrap_or(false);
+    vm.push(ir::Value::Boolean(res))
+}
+
 pub fn gt(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    if let (ir::Value::Number(a), ir::Value::Number(b)) = (ctx.vm.pop()?, ctx.vm.pop()?) {
-        ctx.vm.push(ir::Value::Boolean(a > b))
-    } else {
-        Err(machine::VMError::ExpectedBoolean)
-    }
+    compare(ctx.vm, Ordering::is_gt)
 }
 
 pub fn lt(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    if let (ir::Value::Number(a), ir::Value::Number(b)) = (ctx.vm.pop()?, ctx.vm.pop()?) {
-        ctx.vm.push(ir::Value::Boolean(a < b))
-    } else {
-        Err(machine::VMError::ExpectedBoolean)
-    }
+    compare(ctx.vm, Ordering::is_lt)
 }
 
 pub fn lte(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    if let (ir::Value::Number(a), ir::Value::Number(b)) = (ctx.vm.pop()?, ctx.vm.pop()?) {
-        ctx.vm.push(ir::Value::Boolean(a <= b))
-    } else {
-        Err(machine::VMError::ExpectedBoolean)
-    }
+    compare(ctx.vm, Ordering::is_le)
 }
 
 pub fn gte(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    if let (ir::Value::Number(a), ir::Value::Number(b)) = (ctx.vm.pop()?, ctx.vm.pop()?) {
-        ctx.vm.push(ir::Value::Boolean(a >= b))
-    } else {
-        Err(machine::VMError::ExpectedBoolean)
-    }
+    compare(ctx.vm, Ordering::is_ge)
 }
 
 pub fn clear_stack(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
@@ -357,6 +454,14 @@ define_native_group! {
     ">=" => gte,
     "<=" => lte,
     "pow" => pow,
+    "//" => int_div,
+    "%" => int_rem,
+    "&" => bit_and,
+    "|" => bit_or,
+    "^" => bit_xor,
+    "~" => bit_not,
+    "<<" => shift_left,
+    ">>" => shift_right,
     "nop" => nop,
     "root" => root,
     "==" => equal,
@@ -365,6 +470,9 @@ define_native_group! {
     "@" => deref,
     "=" => set_ref,
     "alloc" => alloc,
+    "downgrade" => downgrade,
+    "upgrade" => upgrade,
+    "weak_alive?" => weak_alive,
     "unsafe_realloc" => realloc,
     "unsafe_free" => free
 }
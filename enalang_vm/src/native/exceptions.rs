@@ -10,11 +10,20 @@ pub fn try_exception(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMErr
         return Err(machine::VMError::ExpectedBlock);
     };
 
-    if let Err(err) = ctx.vm.run_block(&block) {
-        ctx.vm
-            .push(ir::Value::Exception(Box::new(ir::Value::String(
-                local_fmt!("{err:?}"),
-            ))))?;
+    match ctx.vm.run_block(&block) {
+        // exiting is not an error, so it is never caught
+        Err(err @ machine::VMError::Exit(_)) => return Err(err),
+        // thrown values are caught as they are
+        Err(machine::VMError::RuntimeException(value)) => {
+            ctx.vm.push(ir::Value::Exception(Box::new(value)))?;
+        }
+        Err(err) => {
+            ctx.vm
+                .push(ir::Value::Exception(Box::new(ir::Value::String(
+                    local_fmt!("{err:?}"),
+                ))))?;
+        }
+        Ok(_) => {}
     }
 
     Ok(())
@@ -0,0 +1,139 @@
+use crate::{
+    define_native_group,
+    heap::{self, HeapObject},
+    machine, native,
+    native::io::io_error,
+    resources::FileHandle,
+};
+use enalang_ir as ir;
+use flexstr::ToLocalStr;
+use std::fs::{File, OpenOptions};
+
+fn file(vm: &mut machine::VM, pointer: usize) -> Result<&mut FileHandle, machine::VMError> {
+    match vm.heap.get_object_mut(pointer) {
+        Some(HeapObject::File(file)) => Ok(file),
+        Some(_) => Err(machine::VMError::ExpectedFile),
+        None => Err(machine::VMError::BadPointer(pointer)),
+    }
+}
+
+fn with_file<F>(ctx: native::NativeHandlerCtx, f: F) -> Result<(), machine::VMError>
+where
+    F: FnOnce(&mut machine::VM, usize) -> Result<(), machine::VMError>,
+{
+    let pointer = match ctx.vm.stack.pop() {
+        Some(ir::Value::Handle(pointer)) => pointer,
+        Some(other) => {
+            ctx.vm.handle_minus(other)?;
+            return Err(machine::VMError::ExpectedFile);
+        }
+        None => return Err(machine::VMError::StackEnded),
+    };
+
+    let result = f(ctx.vm, pointer);
+    ctx.vm.handle_minus(ir::Value::Handle(pointer))?;
+    result
+}
+
+fn open(
+    ctx: native::NativeHandlerCtx,
+    options: &OpenOptions,
+    handle: fn(File) -> FileHandle,
+) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    let file = options
+        .open(path.as_str())
+        .map_err(|x| io_error(&path, x))?;
+
+    let pointer =
+        heap::heap_result_into_vm(ctx.vm.heap.alloc_object(HeapObject::File(handle(file))))?;
+    ctx.vm.stack.push(ir::Value::Handle(pointer));
+    Ok(())
+}
+
+pub fn open_read(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    open(ctx, OpenOptions::new().read(true), FileHandle::reader)
+}
+
+pub fn open_write(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    open(
+        ctx,
+        OpenOptions::new().write(true).create(true).truncate(true),
+        FileHandle::writer,
+    )
+}
+
+pub fn open_append(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    open(
+        ctx,
+        OpenOptions::new().append(true).create(true),
+        FileHandle::writer,
+    )
+}
+
+pub fn read_line(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_file(ctx, |vm, pointer| {
+        match file(vm, pointer)?
+            .read_line()
+            .map_err(|x| io_error("file", x))?
+        {
+            Some(line) => vm.push(ir::Value::String(line.to_local_str())),
+            None => vm.push(ir::Value::Null),
+        }
+    })
+}
+
+pub fn read(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_file(ctx, |vm, pointer| {
+        let count = vm.pop_pointer()?;
+        let data = file(vm, pointer)?
+            .read(count)
+            .map_err(|x| io_error("file", x))?;
+        vm.push(ir::Value::Bytes(data))
+    })
+}
+
+pub fn write(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_file(ctx, |vm, pointer| {
+        let data = vm.pop_contents()?;
+        file(vm, pointer)?
+            .write(&data)
+            .map_err(|x| io_error("file", x))
+    })
+}
+
+pub fn seek(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_file(ctx, |vm, pointer| {
+        let position = vm.pop_pointer()?;
+        file(vm, pointer)?
+            .seek(position as u64)
+            .map_err(|x| io_error("file", x))?;
+        Ok(())
+    })
+}
+
+pub fn flush(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_file(ctx, |vm, pointer| {
+        file(vm, pointer)?.flush().map_err(|x| io_error("file", x))
+    })
+}
+
+pub fn close(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_file(ctx, |vm, pointer| {
+        file(vm, pointer)?.close().map_err(|x| io_error("file", x))
+    })
+}
+
+define_native_group! {
+    group,
+    "file",
+    "open_read" => open_read,
+    "open_write" => open_write,
+    "open_append" => open_append,
+    "read_line" => read_line,
+    "read" => read,
+    "write" => write,
+    "seek" => seek,
+    "flush" => flush,
+    "close" => close
+}
@@ -0,0 +1,30 @@
+use crate::{define_native_group, machine, native};
+use enalang_ir as ir;
+use flexstr::ToLocalStr;
+use sha2::Digest;
+
+fn digest<D: Digest>(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let data = ctx.vm.pop_contents()?;
+    let hex = hex::encode(D::digest(data));
+    ctx.vm.push(ir::Value::String(hex.to_local_str()))
+}
+
+pub fn sha256(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    digest::<sha2::Sha256>(ctx)
+}
+
+pub fn sha1(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    digest::<sha1::Sha1>(ctx)
+}
+
+pub fn md5(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    digest::<md5::Md5>(ctx)
+}
+
+define_native_group! {
+    group,
+    "hash",
+    "sha256" => sha256,
+    "sha1" => sha1,
+    "md5" => md5
+}
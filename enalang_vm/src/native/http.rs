@@ -0,0 +1,523 @@
+use crate::{
+    collections, define_native_group,
+    heap::{self, HeapObject},
+    machine, native,
+    native::net::{net_error, socket, with_handle},
+    resources::Socket,
+};
+use enalang_ir as ir;
+use flexstr::{local_fmt, LocalStr, ToLocalStr};
+use std::net::TcpStream;
+
+fn http_error(message: impl std::fmt::Display) -> machine::VMError {
+    machine::VMError::RuntimeException(ir::Value::String(local_fmt!("{message}")))
+}
+
+// bodies are read into memory, so their size is limited
+const MAX_BODY_SIZE: usize = 1 << 26;
+
+fn check_body_size(size: usize) -> Result<(), machine::VMError> {
+    if size > MAX_BODY_SIZE {
+        return Err(http_error("body is too large"));
+    }
+    Ok(())
+}
+
+fn reason(status: i64) -> &'static str {
+    match status {
+        100 => "Continue",
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+fn key(name: &str) -> ir::Value {
+    ir::Value::String(name.to_local_str())
+}
+
+// the values already hold their references, which move into the map
+fn alloc_map(
+    vm: &mut machine::VM,
+    entries: Vec<(ir::Value, ir::Value)>,
+) -> Result<ir::Value, machine::VMError> {
+    let mut map = collections::Map::new();
+    for (key, value) in entries {
+        if let Some(old) = map.insert(key, value)? {
+            vm.handle_minus(old)?;
+        }
+    }
+    let pointer = heap::heap_result_into_vm(vm.heap.alloc_object(HeapObject::Map(map)))?;
+    Ok(ir::Value::Map(pointer))
+}
+
+fn body_value(body: Vec<u8>) -> ir::Value {
+    match String::from_utf8(body) {
+        Ok(st) => ir::Value::String(st.to_local_str()),
+        Err(e) => ir::Value::Bytes(e.into_bytes()),
+    }
+}
+
+// requests and responses given to the natives are maps. the map is read and released
+fn with_message<T>(
+    vm: &mut machine::VM,
+    f: impl FnOnce(&heap::Heap, &collections::Map) -> Result<T, machine::VMError>,
+) -> Result<T, machine::VMError> {
+    let pointer = match vm.stack.pop() {
+        Some(ir::Value::Map(pointer)) => pointer,
+        Some(other) => {
+            vm.handle_minus(other)?;
+            return Err(machine::VMError::ExpectedMap);
+        }
+        None => return Err(machine::VMError::StackEnded),
+    };
+
+    let result = match vm.heap.get_object(pointer) {
+        Some(HeapObject::Map(map)) => f(&vm.heap, map),
+        _ => Err(machine::VMError::BadPointer(pointer)),
+    };
+    vm.handle_minus(ir::Value::Map(pointer))?;
+    result
+}
+
+fn get_string(
+    map: &collections::Map,
+    name: &str,
+    default: &str,
+) -> Result<LocalStr, machine::VMError> {
+    match map.get(&key(name))? {
+        Some(ir::Value::String(st)) => Ok(st.clone()),
+        Some(ir::Value::Null) | None => Ok(default.to_local_str()),
+        Some(_) => Err(machine::VMError::ExpectedString),
+    }
+}
+
+fn get_body(map: &collections::Map) -> Result<Vec<u8>, machine::VMError> {
+    match map.get(&key("body"))? {
+        Some(ir::Value::String(st)) => Ok(st.as_bytes().to_vec()),
+        Some(ir::Value::Bytes(bytes)) => Ok(bytes.clone()),
+        Some(ir::Value::Null) | None => Ok(vec![]),
+        Some(_) => Err(machine::VMError::ExpectedString),
+    }
+}
+
+fn get_headers(
+    heap: &heap::Heap,
+    map: &collections::Map,
+) -> Result<Vec<(String, String)>, machine::VMError> {
+    let pointer = match map.get(&key("headers"))? {
+        Some(ir::Value::Map(pointer)) => *pointer,
+        Some(ir::Value::Null) | None => return Ok(vec![]),
+        Some(_) => return Err(machine::VMError::ExpectedMap),
+    };
+
+    let headers = match heap.get_object(pointer) {
+        Some(HeapObject::Map(headers)) => headers,
+        _ => return Err(machine::VMError::BadPointer(pointer)),
+    };
+
+    let mut result = Vec::new();
+    for (name, value) in headers.iter() {
+        let value = match value {
+            ir::Value::String(st) => st.to_string(),
+            ir::Value::Integer(i) => i.to_string(),
+            _ => return Err(machine::VMError::ExpectedString),
+        };
+        match name {
+            ir::Value::String(name) => result.push((name.to_string(), value)),
+            _ => return Err(machine::VMError::ExpectedString),
+        }
+    }
+    Ok(result)
+}
+
+// a line break would end the field early and let the rest pass as another header
+fn check_field(what: &str, value: &str, forbidden: &[char]) -> Result<(), machine::VMError> {
+    if value.contains(forbidden) {
+        return Err(http_error(format_args!(
+            "invalid {what} `{}`",
+            value.escape_debug()
+        )));
+    }
+    Ok(())
+}
+
+fn write_message(
+    start_line: &str,
+    headers: &[(String, String)],
+    body: &[u8],
+) -> Result<Vec<u8>, machine::VMError> {
+    let mut head = format!("{start_line}\r\n");
+    for (name, value) in headers {
+        check_field("header name", name, &['\r', '\n', ':'])?;
+        check_field("header value", value, &['\r', '\n'])?;
+        // the length and connection headers are always set by us
+        if name.eq_ignore_ascii_case("content-length") || name.eq_ignore_ascii_case("connection") {
+            continue;
+        }
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    ));
+
+    let mut message = head.into_bytes();
+    message.extend_from_slice(body);
+    Ok(message)
+}
+
+struct Head {
+    start_line: String,
+    headers: Vec<(String, String)>,
+}
+
+impl Head {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// heads are kept in memory as well, so their lines and headers are limited
+const MAX_LINE_LENGTH: usize = 8192;
+const MAX_HEADERS: usize = 100;
+
+fn read_line(socket: &mut Socket) -> Result<Option<String>, machine::VMError> {
+    let mut line = match socket
+        .read_line_at_most(MAX_LINE_LENGTH)
+        .map_err(|x| net_error("read", x))?
+    {
+        Some(line) => line,
+        None => return Ok(None),
+    };
+
+    if line.last() == Some(&b'\n') {
+        line.pop();
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+    } else if line.len() == MAX_LINE_LENGTH {
+        return Err(http_error("line is too long"));
+    }
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|_| machine::VMError::InvalidUtf8)
+}
+
+fn check_header_count(count: usize) -> Result<(), machine::VMError> {
+    if count > MAX_HEADERS {
+        return Err(http_error("too many headers"));
+    }
+    Ok(())
+}
+
+// returns `None` if the connection was closed before anything was sent
+fn read_head(socket: &mut Socket) -> Result<Option<Head>, machine::VMError> {
+    let start_line = match read_line(socket)? {
+        Some(line) => line,
+        None => return Ok(None),
+    };
+
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for count in 1.. {
+        let line = read_line(socket)?.ok_or_else(|| http_error("unexpected end of headers"))?;
+        if line.is_empty() {
+            break;
+        }
+        check_header_count(count)?;
+
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| http_error(format_args!("invalid header `{line}`")))?;
+        let name = name.trim().to_ascii_lowercase();
+        let value = value.trim();
+        // repeated headers are combined into one
+        match headers.iter_mut().find(|(key, _)| *key == name) {
+            Some((_, old)) => {
+                old.push_str(", ");
+                old.push_str(value);
+            }
+            None => headers.push((name, value.to_string())),
+        }
+    }
+
+    Ok(Some(Head {
+        start_line,
+        headers,
+    }))
+}
+
+fn read_chunked(socket: &mut Socket) -> Result<Vec<u8>, machine::VMError> {
+    let mut body = Vec::new();
+    loop {
+        let line = read_line(socket)?.ok_or_else(|| http_error("unexpected end of body"))?;
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16)
+            .map_err(|_| http_error(format_args!("invalid chunk size `{size}`")))?;
+        if size == 0 {
+            // trailers end with an empty line
+            for count in 1.. {
+                match read_line(socket)? {
+                    Some(line) if !line.is_empty() => check_header_count(count)?,
+                    _ => break,
+                }
+            }
+            return Ok(body);
+        }
+
+        check_body_size(body.len().saturating_add(size))?;
+        body.extend(socket.read_exact(size).map_err(|x| net_error("read", x))?);
+        read_line(socket)?;
+    }
+}
+
+fn read_body(
+    socket: &mut Socket,
+    head: &Head,
+    until_closed: bool,
+) -> Result<Vec<u8>, machine::VMError> {
+    if head
+        .header("transfer-encoding")
+        .is_some_and(|x| x.eq_ignore_ascii_case("chunked"))
+    {
+        return read_chunked(socket);
+    }
+
+    match head.header("content-length") {
+        Some(length) => {
+            let length = length
+                .parse()
+                .map_err(|_| http_error(format_args!("invalid content length `{length}`")))?;
+            check_body_size(length)?;
+            socket.read_exact(length).map_err(|x| net_error("read", x))
+        }
+        None if until_closed => {
+            let body = socket
+                .read_at_most(MAX_BODY_SIZE + 1)
+                .map_err(|x| net_error("read", x))?;
+            check_body_size(body.len())?;
+            Ok(body)
+        }
+        None => Ok(vec![]),
+    }
+}
+
+fn headers_value(
+    vm: &mut machine::VM,
+    headers: Vec<(String, String)>,
+) -> Result<ir::Value, machine::VMError> {
+    let entries = headers
+        .into_iter()
+        .map(|(name, value)| (key(&name), ir::Value::String(value.to_local_str())))
+        .collect();
+    alloc_map(vm, entries)
+}
+
+struct Response {
+    status: i64,
+    reason: LocalStr,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+fn read_response_from(socket: &mut Socket) -> Result<Response, machine::VMError> {
+    let head = read_head(socket)?.ok_or_else(|| http_error("connection closed"))?;
+    let mut parts = head.start_line.splitn(3, ' ');
+    let status = match (parts.next(), parts.next()) {
+        (Some(version), Some(status)) if version.starts_with("HTTP/") => status
+            .parse::<i64>()
+            .map_err(|_| http_error(format_args!("invalid status `{status}`")))?,
+        _ => {
+            return Err(http_error(format_args!(
+                "invalid status line `{}`",
+                head.start_line
+            )))
+        }
+    };
+    let reason = parts.next().unwrap_or_default().to_local_str();
+    let body = read_body(socket, &head, true)?;
+
+    Ok(Response {
+        status,
+        reason,
+        headers: head.headers,
+        body,
+    })
+}
+
+fn push_response(vm: &mut machine::VM, response: Response) -> Result<(), machine::VMError> {
+    let headers = headers_value(vm, response.headers)?;
+    let response = alloc_map(
+        vm,
+        vec![
+            (key("status"), ir::Value::Integer(response.status)),
+            (key("reason"), ir::Value::String(response.reason)),
+            (key("headers"), headers),
+            (key("body"), body_value(response.body)),
+        ],
+    )?;
+    vm.stack.push(response);
+    Ok(())
+}
+
+fn request_message(
+    heap: &heap::Heap,
+    map: &collections::Map,
+    host: &str,
+    path: &str,
+) -> Result<Vec<u8>, machine::VMError> {
+    let method = get_string(map, "method", "GET")?;
+    let path = get_string(map, "path", path)?;
+    check_field("method", &method, &['\r', '\n'])?;
+    check_field("path", &path, &['\r', '\n'])?;
+    let mut headers = vec![(String::from("Host"), host.to_string())];
+    headers.extend(get_headers(heap, map)?);
+    write_message(
+        &format!("{method} {path} HTTP/1.1"),
+        &headers,
+        &get_body(map)?,
+    )
+}
+
+pub fn read_request(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_handle(ctx, |vm, pointer| {
+        let head = match read_head(socket(vm, pointer)?)? {
+            Some(head) => head,
+            None => return vm.push(ir::Value::Null),
+        };
+
+        let mut parts = head.start_line.split(' ');
+        let (method, target, version) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/") => {
+                (method, target, version)
+            }
+            _ => {
+                return Err(http_error(format_args!(
+                    "invalid request line `{}`",
+                    head.start_line
+                )))
+            }
+        };
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path, ir::Value::String(query.to_local_str())),
+            None => (target, ir::Value::Null),
+        };
+        let mut entries = vec![
+            (key("method"), ir::Value::String(method.to_local_str())),
+            (key("path"), ir::Value::String(path.to_local_str())),
+            (key("query"), query),
+            (key("version"), ir::Value::String(version.to_local_str())),
+        ];
+
+        let body = read_body(socket(vm, pointer)?, &head, false)?;
+        entries.push((key("headers"), headers_value(vm, head.headers)?));
+        entries.push((key("body"), body_value(body)));
+        let request = alloc_map(vm, entries)?;
+        vm.stack.push(request);
+        Ok(())
+    })
+}
+
+pub fn write_response(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_handle(ctx, |vm, pointer| {
+        let message = with_message(vm, |heap, map| {
+            let status = match map.get(&key("status"))? {
+                Some(ir::Value::Integer(status)) => *status,
+                Some(ir::Value::Null) | None => 200,
+                Some(_) => return Err(machine::VMError::ExpectedInteger),
+            };
+            write_message(
+                &format!("HTTP/1.1 {status} {}", reason(status)),
+                &get_headers(heap, map)?,
+                &get_body(map)?,
+            )
+        })?;
+        socket(vm, pointer)?
+            .write(&message)
+            .map_err(|x| net_error("write", x))
+    })
+}
+
+pub fn write_request(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_handle(ctx, |vm, pointer| {
+        let address = socket(vm, pointer)?
+            .peer_addr()
+            .map_err(|x| net_error("peer_addr", x))?
+            .to_string();
+        let message = with_message(vm, |heap, map| {
+            let host = get_string(map, "host", &address)?;
+            request_message(heap, map, &host, "/")
+        })?;
+        socket(vm, pointer)?
+            .write(&message)
+            .map_err(|x| net_error("write", x))
+    })
+}
+
+pub fn read_response(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_handle(ctx, |vm, pointer| {
+        let response = read_response_from(socket(vm, pointer)?)?;
+        push_response(vm, response)
+    })
+}
+
+// `http://host[:port][/path]`, the path keeps its query
+fn parse_url(url: &str) -> Result<(String, String, String), machine::VMError> {
+    let rest = match url.split_once("://") {
+        Some(("http", rest)) => rest,
+        Some((scheme, _)) => return Err(http_error(format_args!("unsupported scheme `{scheme}`"))),
+        None => url,
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    if authority.is_empty() {
+        return Err(http_error(format_args!("invalid url `{url}`")));
+    }
+    let address = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{authority}:80")
+    };
+    Ok((address, authority.to_string(), path.to_string()))
+}
+
+pub fn fetch(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let url = ctx.vm.pop_string()?;
+    let (address, host, path) = parse_url(&url)?;
+    let message = with_message(ctx.vm, |heap, map| request_message(heap, map, &host, &path))?;
+
+    let mut socket = TcpStream::connect(&address)
+        .and_then(Socket::new)
+        .map_err(|x| net_error(&address, x))?;
+    socket.write(&message).map_err(|x| net_error(&address, x))?;
+    let response = read_response_from(&mut socket)?;
+    push_response(ctx.vm, response)
+}
+
+define_native_group! {
+    group,
+    "http",
+    "read_request" => read_request,
+    "write_response" => write_response,
+    "write_request" => write_request,
+    "read_response" => read_response,
+    "fetch" => fetch
+}
@@ -1,31 +1,122 @@
-use crate::{define_native_group, machine::{self, VMError}, native};
+use crate::{define_native_group, machine, native, resources};
 use enalang_ir as ir;
 use flexstr::{local_fmt, ToLocalStr};
 use ir::Value;
-use std::{fs, path::Path};
+use std::{fs, io::Write, path::Path, time::UNIX_EPOCH};
+
+// i/o failures are raised as exceptions, so they can be caught with `try`
+pub(crate) fn io_error(path: &str, err: std::io::Error) -> machine::VMError {
+    machine::VMError::RuntimeException(Value::String(local_fmt!("{path}: {err}")))
+}
 
 pub fn print(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    if let ir::Value::String(st) = ctx.vm.pop()? {
-        print!("{st}");
-    } else {
-        return Err(machine::VMError::ExpectedString);
+    let st = ctx.vm.pop_string()?;
+    write!(ctx.vm.io.stdout(), "{st}").map_err(|x| io_error("stdout", x))
+}
+
+pub fn eprint(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    write!(ctx.vm.io.stderr(), "{st}").map_err(|x| io_error("stderr", x))
+}
+
+pub fn read_line(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let line = resources::read_line(&mut *ctx.vm.io.stdin()).map_err(|x| io_error("stdin", x))?;
+
+    match line {
+        Some(line) => ctx.vm.push(Value::String(line.to_local_str())),
+        None => ctx.vm.push(Value::Null),
     }
+}
 
-    Ok(())
+pub fn read_all(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let mut str = String::new();
+    ctx.vm
+        .io
+        .stdin()
+        .read_to_string(&mut str)
+        .map_err(|x| io_error("stdin", x))?;
+    ctx.vm.push(Value::String(str.to_local_str()))
+}
+
+pub fn is_eof(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let eof = ctx
+        .vm
+        .io
+        .stdin()
+        .fill_buf()
+        .map(|x| x.is_empty())
+        .map_err(|x| io_error("stdin", x))?;
+    ctx.vm.push(Value::Boolean(eof))
 }
 
 pub fn read_file(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    if let ir::Value::String(st) = ctx.vm.pop()? {
-        let str = fs::read_to_string::<String>(format!("{st}"))
-            .map_err(|x| VMError::FS(local_fmt!("{x}")))?;
-        ctx.vm.push(Value::String(local_fmt!("{str}")))?;
-    } else {
-        return Err(machine::VMError::ExpectedString);
-    }
+    let path = ctx.vm.pop_string()?;
+    let str = fs::read_to_string(path.as_str()).map_err(|x| io_error(&path, x))?;
+    ctx.vm.push(Value::String(str.to_local_str()))
+}
+
+pub fn read_file_bytes(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    let bytes = fs::read(path.as_str()).map_err(|x| io_error(&path, x))?;
+    ctx.vm.push(Value::Bytes(bytes))
+}
+
+pub fn write_file(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    let contents = ctx.vm.pop_contents()?;
+    fs::write(path.as_str(), contents).map_err(|x| io_error(&path, x))
+}
+
+pub fn write_file_bytes(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    let bytes = ctx.vm.pop_bytes()?;
+    fs::write(path.as_str(), bytes).map_err(|x| io_error(&path, x))
+}
 
+pub fn append_file(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    let contents = ctx.vm.pop_contents()?;
+    fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path.as_str())
+        .and_then(|mut file| file.write_all(&contents))
+        .map_err(|x| io_error(&path, x))
+}
+
+pub fn delete_file(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    fs::remove_file(path.as_str()).map_err(|x| io_error(&path, x))
+}
+
+pub fn rename(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let from = ctx.vm.pop_string()?;
+    let to = ctx.vm.pop_string()?;
+    fs::rename(from.as_str(), to.as_str()).map_err(|x| io_error(&from, x))
+}
+
+pub fn copy_file(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let from = ctx.vm.pop_string()?;
+    let to = ctx.vm.pop_string()?;
+    fs::copy(from.as_str(), to.as_str()).map_err(|x| io_error(&from, x))?;
     Ok(())
 }
 
+pub fn create_dir(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    fs::create_dir_all(path.as_str()).map_err(|x| io_error(&path, x))
+}
+
+pub fn remove_dir(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    fs::remove_dir(path.as_str()).map_err(|x| io_error(&path, x))
+}
+
+pub fn remove_dir_all(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    fs::remove_dir_all(path.as_str()).map_err(|x| io_error(&path, x))
+}
+
 pub fn file_exists(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     if let ir::Value::String(st) = ctx.vm.pop()? {
         ctx.vm
@@ -35,44 +126,122 @@ pub fn file_exists(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError
     }
 }
 
+pub fn dir_exists(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    ctx.vm
+        .push(ir::Value::Boolean(Path::new(path.as_str()).is_dir()))
+}
+
+pub fn file_size(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    let metadata = fs::metadata(path.as_str()).map_err(|x| io_error(&path, x))?;
+    ctx.vm.push(ir::Value::Integer(metadata.len() as i64))
+}
+
+pub fn file_modified(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    let modified = fs::metadata(path.as_str())
+        .and_then(|x| x.modified())
+        .map_err(|x| io_error(&path, x))?;
+
+    let seconds = match modified.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+    ctx.vm.push(ir::Value::Integer(seconds))
+}
+
 pub fn files_in_dir(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    if let ir::Value::String(st) = ctx.vm.pop()? {
-        let res = fs::read_dir(st.as_str());
-        let files = match res {
-            Ok(i) => i,
-            Err(e) => {
-                return Err(machine::VMError::RuntimeException(ir::Value::String(
-                    local_fmt!("{e:?}"),
-                )));
-            }
-        };
-        let mut total_files = 0;
-        for file in files {
-            let file = match file {
-                Ok(i) => i,
-                Err(e) => {
-                    return Err(machine::VMError::RuntimeException(ir::Value::String(
-                        local_fmt!("{e:?}"),
-                    )));
-                }
-            };
-            ctx.vm.push(ir::Value::String(
-                file.file_name().to_str().unwrap().to_local_str(),
-            ))?;
-            total_files += 1;
-        }
-        ctx.vm.push(ir::Value::Number(total_files as f64))?;
-        Ok(())
-    } else {
-        Err(machine::VMError::ExpectedString)
+    let path = ctx.vm.pop_string()?;
+    let files = fs::read_dir(path.as_str()).map_err(|x| io_error(&path, x))?;
+
+    let mut total_files = 0;
+    for file in files {
+        let file = file.map_err(|x| io_error(&path, x))?;
+        ctx.vm.push(ir::Value::String(
+            file.file_name().to_string_lossy().to_local_str(),
+        ))?;
+        total_files += 1;
     }
+
+    ctx.vm.push(ir::Value::Integer(total_files))
+}
+
+pub fn path_join(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let base = ctx.vm.pop_string()?;
+    let child = ctx.vm.pop_string()?;
+    let joined = Path::new(base.as_str()).join(child.as_str());
+    ctx.vm
+        .push(ir::Value::String(joined.to_string_lossy().to_local_str()))
+}
+
+fn push_path_part(
+    vm: &mut machine::VM,
+    part: Option<&std::ffi::OsStr>,
+) -> Result<(), machine::VMError> {
+    match part {
+        Some(part) => vm.push(ir::Value::String(part.to_string_lossy().to_local_str())),
+        None => vm.push(ir::Value::Null),
+    }
+}
+
+pub fn path_dirname(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    push_path_part(
+        ctx.vm,
+        Path::new(path.as_str()).parent().map(|x| x.as_os_str()),
+    )
+}
+
+pub fn path_basename(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    push_path_part(ctx.vm, Path::new(path.as_str()).file_name())
+}
+
+pub fn path_extension(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    push_path_part(ctx.vm, Path::new(path.as_str()).extension())
+}
+
+pub fn current_dir(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let dir = std::env::current_dir().map_err(|x| io_error(".", x))?;
+    ctx.vm
+        .push(ir::Value::String(dir.to_string_lossy().to_local_str()))
+}
+
+pub fn set_current_dir(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = ctx.vm.pop_string()?;
+    std::env::set_current_dir(path.as_str()).map_err(|x| io_error(&path, x))
 }
 
 define_native_group! {
     group,
     "ena.vm.io",
     "print" => print,
+    "eprint" => eprint,
+    "read_line" => read_line,
+    "read_all" => read_all,
+    "eof?" => is_eof,
     "read_file" => read_file,
+    "read_file_bytes" => read_file_bytes,
+    "write_file" => write_file,
+    "write_file_bytes" => write_file_bytes,
+    "append_file" => append_file,
+    "delete_file" => delete_file,
+    "rename" => rename,
+    "copy_file" => copy_file,
+    "create_dir" => create_dir,
+    "remove_dir" => remove_dir,
+    "remove_dir_all" => remove_dir_all,
     "file_exists?" => file_exists,
-    "list_files_in_dir" => files_in_dir
+    "dir_exists?" => dir_exists,
+    "file_size" => file_size,
+    "file_modified" => file_modified,
+    "list_files_in_dir" => files_in_dir,
+    "path_join" => path_join,
+    "path_dirname" => path_dirname,
+    "path_basename" => path_basename,
+    "path_extension" => path_extension,
+    "current_dir" => current_dir,
+    "set_current_dir" => set_current_dir
 }
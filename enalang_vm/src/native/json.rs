@@ -0,0 +1,267 @@
+use crate::{
+    collections, define_native_group,
+    heap::{self, HeapObject},
+    machine, native,
+};
+use enalang_ir as ir;
+use flexstr::{local_fmt, ToLocalStr};
+use ir::BigInt;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::fmt;
+
+// parsed documents are built as a tree first, so invalid input never leaves
+// half-built arrays on the heap. object keys keep their order
+enum Json {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    BigInteger(BigInt),
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+struct JsonVisitor;
+
+impl<'de> Visitor<'de> for JsonVisitor {
+    type Value = Json;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a json value")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Json, E> {
+        Ok(Json::Boolean(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Json, E> {
+        Ok(Json::Integer(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Json, E> {
+        Ok(match i64::try_from(v) {
+            Ok(v) => Json::Integer(v),
+            Err(_) => Json::BigInteger(BigInt::from(v)),
+        })
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Json, E> {
+        Ok(Json::Float(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Json, E> {
+        Ok(Json::String(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Json, E> {
+        Ok(Json::String(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Json, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(Json::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Json, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Json::Object(entries))
+    }
+}
+
+impl<'de> Deserialize<'de> for Json {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(JsonVisitor)
+    }
+}
+
+// the returned value holds one reference, like a freshly allocated object
+fn into_value(vm: &mut machine::VM, json: Json) -> Result<ir::Value, machine::VMError> {
+    Ok(match json {
+        Json::Null => ir::Value::Null,
+        Json::Boolean(b) => ir::Value::Boolean(b),
+        Json::Integer(i) => ir::Value::Integer(i),
+        Json::BigInteger(i) => ir::Value::integer(i),
+        Json::Float(n) => ir::Value::Number(n),
+        Json::String(st) => ir::Value::String(st.to_local_str()),
+        Json::Array(items) => {
+            let mut values = Vec::with_capacity(items.len());
+            for item in items {
+                values.push(into_value(vm, item)?);
+            }
+            let pointer =
+                heap::heap_result_into_vm(vm.heap.alloc_object(HeapObject::Array(values)))?;
+            ir::Value::Array(pointer)
+        }
+        Json::Object(entries) => {
+            let mut map = collections::Map::new();
+            for (key, value) in entries {
+                let value = into_value(vm, value)?;
+                // duplicate keys keep the last value
+                if let Some(old) = map.insert(ir::Value::String(key.to_local_str()), value)? {
+                    vm.handle_minus(old)?;
+                }
+            }
+            let pointer = heap::heap_result_into_vm(vm.heap.alloc_object(HeapObject::Map(map)))?;
+            ir::Value::Map(pointer)
+        }
+    })
+}
+
+struct Writer<'a> {
+    heap: &'a heap::Heap,
+    indent: Option<&'a str>,
+    out: String,
+    seen: Vec<usize>,
+}
+
+impl<'a> Writer<'a> {
+    fn newline(&mut self, depth: usize) {
+        if let Some(indent) = self.indent {
+            self.out.push('\n');
+            for _ in 0..depth {
+                self.out.push_str(indent);
+            }
+        }
+    }
+
+    fn write_string(&mut self, st: &str) {
+        self.out
+            .push_str(&serde_json::to_string(st).unwrap_or_default());
+    }
+
+    fn write_key(&mut self, key: &ir::Value) -> Result<(), String> {
+        match key {
+            ir::Value::String(st) => self.write_string(st),
+            ir::Value::Atom(atom) => self.write_string(atom),
+            ir::Value::Integer(i) => self.write_string(&i.to_string()),
+            ir::Value::BigInteger(i) => self.write_string(&i.to_string()),
+            ir::Value::Boolean(b) => self.write_string(&b.to_string()),
+            other => return Err(format!("cannot use {other:?} as a json key")),
+        }
+        Ok(())
+    }
+
+    fn write_items<T>(
+        &mut self,
+        brackets: (char, char),
+        items: &[T],
+        depth: usize,
+        mut write_item: impl FnMut(&mut Self, &T) -> Result<(), String>,
+    ) -> Result<(), String> {
+        self.out.push(brackets.0);
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            self.newline(depth + 1);
+            write_item(self, item)?;
+        }
+        if !items.is_empty() {
+            self.newline(depth);
+        }
+        self.out.push(brackets.1);
+        Ok(())
+    }
+
+    fn write(&mut self, value: &ir::Value, depth: usize) -> Result<(), String> {
+        match value {
+            ir::Value::Null => self.out.push_str("null"),
+            ir::Value::Boolean(b) => self.out.push_str(&b.to_string()),
+            ir::Value::Integer(i) => self.out.push_str(&i.to_string()),
+            ir::Value::BigInteger(i) => self.out.push_str(&i.to_string()),
+            ir::Value::Number(n) => match serde_json::Number::from_f64(*n) {
+                Some(n) => self.out.push_str(&n.to_string()),
+                None => return Err(format!("cannot convert {n} to json")),
+            },
+            ir::Value::String(st) => self.write_string(st),
+            ir::Value::Atom(atom) => self.write_string(atom),
+            ir::Value::Array(pointer) | ir::Value::Map(pointer) if self.seen.contains(pointer) => {
+                return Err(String::from("cannot convert a cyclic value to json"));
+            }
+            ir::Value::Array(pointer) | ir::Value::Map(pointer) => {
+                self.seen.push(*pointer);
+                let heap = self.heap;
+                match heap.get_object(*pointer) {
+                    Some(HeapObject::Array(values)) => {
+                        self.write_items(('[', ']'), values, depth, |w, value| {
+                            w.write(value, depth + 1)
+                        })?
+                    }
+                    Some(HeapObject::Map(map)) => {
+                        let entries: Vec<_> = map.iter().collect();
+                        self.write_items(('{', '}'), &entries, depth, |w, (key, value)| {
+                            w.write_key(key)?;
+                            w.out.push(':');
+                            if w.indent.is_some() {
+                                w.out.push(' ');
+                            }
+                            w.write(value, depth + 1)
+                        })?
+                    }
+                    _ => return Err(format!("bad pointer {pointer}")),
+                }
+                self.seen.pop();
+            }
+            other => return Err(format!("cannot convert {other:?} to json")),
+        }
+        Ok(())
+    }
+}
+
+fn json_error(message: impl fmt::Display) -> machine::VMError {
+    machine::VMError::RuntimeException(ir::Value::String(local_fmt!("{message}")))
+}
+
+pub fn parse(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    let json: Json =
+        serde_json::from_str(&st).map_err(|err| json_error(format_args!("invalid json: {err}")))?;
+    let value = into_value(ctx.vm, json)?;
+    ctx.vm.stack.push(value);
+    Ok(())
+}
+
+fn stringify_with(
+    ctx: native::NativeHandlerCtx,
+    indent: Option<&str>,
+) -> Result<(), machine::VMError> {
+    let value = ctx.vm.stack.pop().ok_or(machine::VMError::StackEnded)?;
+    let mut writer = Writer {
+        heap: &ctx.vm.heap,
+        indent,
+        out: String::new(),
+        seen: Vec::new(),
+    };
+    let result = writer.write(&value, 0).map(|_| writer.out);
+    ctx.vm.handle_minus(value)?;
+    ctx.vm.push(ir::Value::String(
+        result.map_err(json_error)?.to_local_str(),
+    ))
+}
+
+pub fn stringify(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    stringify_with(ctx, None)
+}
+
+pub fn stringify_pretty(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    stringify_with(ctx, Some("  "))
+}
+
+define_native_group! {
+    group,
+    "json",
+    "parse" => parse,
+    "stringify" => stringify,
+    "stringify_pretty" => stringify_pretty
+}
@@ -0,0 +1,159 @@
+use crate::{
+    collections, define_native_group,
+    heap::{self, HeapObject},
+    machine, native,
+};
+use enalang_ir as ir;
+
+fn map(vm: &mut machine::VM, pointer: usize) -> Result<&mut collections::Map, machine::VMError> {
+    match vm.heap.get_object_mut(pointer) {
+        Some(HeapObject::Map(map)) => Ok(map),
+        _ => Err(machine::VMError::BadPointer(pointer)),
+    }
+}
+
+fn with_map<F>(ctx: native::NativeHandlerCtx, f: F) -> Result<(), machine::VMError>
+where
+    F: FnOnce(&mut machine::VM, usize) -> Result<(), machine::VMError>,
+{
+    let pointer = match ctx.vm.stack.pop() {
+        Some(ir::Value::Map(pointer)) => pointer,
+        Some(other) => {
+            ctx.vm.handle_minus(other)?;
+            return Err(machine::VMError::ExpectedMap);
+        }
+        None => return Err(machine::VMError::StackEnded),
+    };
+
+    let result = f(ctx.vm, pointer);
+    ctx.vm.handle_minus(ir::Value::Map(pointer))?;
+    result
+}
+
+fn push_array(vm: &mut machine::VM, values: Vec<ir::Value>) -> Result<(), machine::VMError> {
+    for value in &values {
+        vm.handle_plus(value.clone())?;
+    }
+
+    let pointer = heap::heap_result_into_vm(vm.heap.alloc_object(HeapObject::Array(values)))?;
+    vm.stack.push(ir::Value::Array(pointer));
+    Ok(())
+}
+
+pub fn new(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let pointer = heap::heap_result_into_vm(
+        ctx.vm
+            .heap
+            .alloc_object(HeapObject::Map(collections::Map::new())),
+    )?;
+    ctx.vm.stack.push(ir::Value::Map(pointer));
+    Ok(())
+}
+
+pub fn insert(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_map(ctx, |vm, pointer| {
+        let key = vm.stack.pop().ok_or(machine::VMError::StackEnded)?;
+        let value = vm.stack.pop().ok_or(machine::VMError::StackEnded)?;
+
+        match map(vm, pointer)?.insert(key.clone(), value.clone()) {
+            Ok(Some(old)) => {
+                vm.handle_minus(old)?;
+                vm.handle_minus(key)
+            }
+            Ok(None) => Ok(()),
+            Err(e) => {
+                vm.handle_minus(key)?;
+                vm.handle_minus(value)?;
+                Err(e)
+            }
+        }
+    })
+}
+
+pub fn get(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_map(ctx, |vm, pointer| {
+        let key = vm.pop()?;
+        let value = map(vm, pointer)?
+            .get(&key)?
+            .cloned()
+            .unwrap_or(ir::Value::Null);
+        vm.push(value)
+    })
+}
+
+pub fn remove(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_map(ctx, |vm, pointer| {
+        let key = vm.pop()?;
+
+        match map(vm, pointer)?.remove(&key)? {
+            Some((key, value)) => {
+                vm.handle_minus(key)?;
+                vm.stack.push(value);
+                Ok(())
+            }
+            None => vm.push(ir::Value::Null),
+        }
+    })
+}
+
+pub fn has(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_map(ctx, |vm, pointer| {
+        let key = vm.pop()?;
+        let has = map(vm, pointer)?.contains_key(&key)?;
+        vm.push(ir::Value::Boolean(has))
+    })
+}
+
+pub fn keys(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_map(ctx, |vm, pointer| {
+        let keys = map(vm, pointer)?.keys().cloned().collect();
+        push_array(vm, keys)
+    })
+}
+
+pub fn values(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_map(ctx, |vm, pointer| {
+        let values = map(vm, pointer)?.values().cloned().collect();
+        push_array(vm, values)
+    })
+}
+
+pub fn len(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_map(ctx, |vm, pointer| {
+        let len = map(vm, pointer)?.len();
+        vm.push(ir::Value::Integer(len as i64))
+    })
+}
+
+pub fn each(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_map(ctx, |vm, pointer| {
+        let block = match vm.pop()? {
+            ir::Value::Block(block) => block,
+            _ => return Err(machine::VMError::ExpectedBlock),
+        };
+
+        let mut i = 0;
+        while let Some((key, value)) = map(vm, pointer)?.entry_at(i).cloned() {
+            vm.push(value)?;
+            vm.push(key)?;
+            vm.run_block(&block)?;
+            i += 1;
+        }
+
+        Ok(())
+    })
+}
+
+define_native_group! {
+    group,
+    "map",
+    "new" => new,
+    "insert" => insert,
+    "get" => get,
+    "remove" => remove,
+    "has?" => has,
+    "keys" => keys,
+    "values" => values,
+    "len" => len,
+    "each" => each
+}
@@ -0,0 +1,190 @@
+use crate::{
+    define_native_group, machine, native,
+    number::{self, Number},
+};
+use enalang_ir as ir;
+use std::cmp::Ordering;
+
+fn float_fn(ctx: native::NativeHandlerCtx, f: fn(f64) -> f64) -> Result<(), machine::VMError> {
+    let a = ctx.vm.pop_number()?;
+    ctx.vm.push(ir::Value::Number(f(a.as_f64())))
+}
+
+fn round_fn(ctx: native::NativeHandlerCtx, f: fn(f64) -> f64) -> Result<(), machine::VMError> {
+    let a = ctx.vm.pop_number()?;
+    ctx.vm.push(number::round_with(&a, f).into_value())
+}
+
+fn float_test(ctx: native::NativeHandlerCtx, f: fn(f64) -> bool) -> Result<(), machine::VMError> {
+    let a = ctx.vm.pop_number()?;
+    let result = match a {
+        Number::Float(n) => f(n),
+        _ => f(0.0),
+    };
+    ctx.vm.push(ir::Value::Boolean(result))
+}
+
+fn pick(ctx: native::NativeHandlerCtx, keep: Ordering) -> Result<(), machine::VMError> {
+    let a = ctx.vm.pop_number()?;
+    let b = ctx.vm.pop_number()?;
+
+    let result = match number::compare(&a, &b) {
+        Some(ord) if ord == keep => a,
+        Some(_) => b,
+        None => Number::Float(f64::NAN),
+    };
+    ctx.vm.push(result.into_value())
+}
+
+pub fn modulo(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let a = ctx.vm.pop_number()?;
+    let b = ctx.vm.pop_number()?;
+    ctx.vm.push(number::rem(&a, &b)?.into_value())
+}
+
+pub fn abs(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let a = ctx.vm.pop_number()?;
+    ctx.vm.push(number::abs(&a).into_value())
+}
+
+pub fn floor(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    round_fn(ctx, f64::floor)
+}
+
+pub fn ceil(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    round_fn(ctx, f64::ceil)
+}
+
+pub fn round(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    round_fn(ctx, f64::round)
+}
+
+pub fn trunc(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    round_fn(ctx, f64::trunc)
+}
+
+pub fn min(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    pick(ctx, Ordering::Less)
+}
+
+pub fn max(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    pick(ctx, Ordering::Greater)
+}
+
+pub fn sin(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    float_fn(ctx, f64::sin)
+}
+
+pub fn cos(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    float_fn(ctx, f64::cos)
+}
+
+pub fn tan(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    float_fn(ctx, f64::tan)
+}
+
+pub fn asin(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    float_fn(ctx, f64::asin)
+}
+
+pub fn acos(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    float_fn(ctx, f64::acos)
+}
+
+pub fn atan(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    float_fn(ctx, f64::atan)
+}
+
+pub fn atan2(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let y = ctx.vm.pop_number()?;
+    let x = ctx.vm.pop_number()?;
+    ctx.vm.push(ir::Value::Number(y.as_f64().atan2(x.as_f64())))
+}
+
+pub fn exp(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    float_fn(ctx, f64::exp)
+}
+
+pub fn ln(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    float_fn(ctx, f64::ln)
+}
+
+pub fn log2(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    float_fn(ctx, f64::log2)
+}
+
+pub fn log10(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    float_fn(ctx, f64::log10)
+}
+
+pub fn log(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let a = ctx.vm.pop_number()?;
+    let base = ctx.vm.pop_number()?;
+    ctx.vm
+        .push(ir::Value::Number(a.as_f64().log(base.as_f64())))
+}
+
+pub fn is_nan(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    float_test(ctx, f64::is_nan)
+}
+
+pub fn is_infinite(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    float_test(ctx, f64::is_infinite)
+}
+
+pub fn is_finite(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    float_test(ctx, f64::is_finite)
+}
+
+pub fn pi(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    ctx.vm.push(ir::Value::Number(std::f64::consts::PI))
+}
+
+pub fn tau(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    ctx.vm.push(ir::Value::Number(std::f64::consts::TAU))
+}
+
+pub fn e(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    ctx.vm.push(ir::Value::Number(std::f64::consts::E))
+}
+
+pub fn infinity(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    ctx.vm.push(ir::Value::Number(f64::INFINITY))
+}
+
+pub fn nan(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    ctx.vm.push(ir::Value::Number(f64::NAN))
+}
+
+define_native_group! {
+    group,
+    "math",
+    "mod" => modulo,
+    "abs" => abs,
+    "floor" => floor,
+    "ceil" => ceil,
+    "round" => round,
+    "trunc" => trunc,
+    "min" => min,
+    "max" => max,
+    "sin" => sin,
+    "cos" => cos,
+    "tan" => tan,
+    "asin" => asin,
+    "acos" => acos,
+    "atan" => atan,
+    "atan2" => atan2,
+    "exp" => exp,
+    "ln" => ln,
+    "log2" => log2,
+    "log10" => log10,
+    "log" => log,
+    "nan?" => is_nan,
+    "infinite?" => is_infinite,
+    "finite?" => is_finite,
+    "pi" => pi,
+    "tau" => tau,
+    "e" => e,
+    "infinity" => infinity,
+    "nan" => nan
+}
@@ -1,21 +1,116 @@
-use crate::machine;
+use crate::{
+    heap::{self, HeapObject, HostObject},
+    machine,
+};
 use enalang_ir as ir;
 use flexstr::{local_fmt, LocalStr, ToLocalStr};
-use std::collections::HashMap;
+use std::{any::Any, cell::RefCell, collections::HashMap, rc::Rc};
 
+pub use enalang_native_macro::ena_native;
+
+pub mod array;
+pub mod bytes;
 pub mod core;
 pub mod exceptions;
+pub mod file;
+pub mod hash;
+pub mod http;
 pub mod io;
+pub mod json;
+pub mod map;
+pub mod math;
+pub mod net;
 pub mod os;
+pub mod regex;
 pub mod strings;
+pub mod term;
+pub mod time;
 pub mod types;
+pub mod url;
 pub mod vm;
 
 pub struct NativeHandlerCtx<'a> {
     pub vm: &'a mut machine::VM,
 }
 
-pub type NativeHandler = fn(ctx: NativeHandlerCtx) -> Result<(), machine::VMError>;
+fn expected_host<T: Any>() -> machine::VMError {
+    machine::VMError::ExpectedHost(std::any::type_name::<T>().to_local_str())
+}
+
+impl NativeHandlerCtx<'_> {
+    // moves a rust value into the heap and pushes it as a host object
+    pub fn push_host<T: Any>(&mut self, type_name: &str, value: T) -> Result<(), machine::VMError> {
+        let object = HeapObject::Host(HostObject::new(type_name, value));
+        let pointer = heap::heap_result_into_vm(self.vm.heap.alloc_object(object))?;
+        self.vm.stack.push(ir::Value::Host(pointer));
+        Ok(())
+    }
+
+    pub fn host<T: Any>(&self, pointer: usize) -> Result<&T, machine::VMError> {
+        match self.vm.heap.get_object(pointer) {
+            Some(HeapObject::Host(object)) => object.downcast_ref().ok_or_else(expected_host::<T>),
+            Some(_) => Err(expected_host::<T>()),
+            None => Err(machine::VMError::BadPointer(pointer)),
+        }
+    }
+
+    pub fn host_mut<T: Any>(&mut self, pointer: usize) -> Result<&mut T, machine::VMError> {
+        match self.vm.heap.get_object_mut(pointer) {
+            Some(HeapObject::Host(object)) => object.downcast_mut().ok_or_else(expected_host::<T>),
+            Some(_) => Err(expected_host::<T>()),
+            None => Err(machine::VMError::BadPointer(pointer)),
+        }
+    }
+
+    // pops a host object and runs `f` with its value. the object is released afterwards,
+    // so it is freed if nothing else refers to it
+    pub fn with_host<T: Any, R>(
+        &mut self,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Result<R, machine::VMError> {
+        let pointer = match self.vm.stack.pop() {
+            Some(ir::Value::Host(pointer)) => pointer,
+            Some(other) => {
+                self.vm.handle_minus(other)?;
+                return Err(expected_host::<T>());
+            }
+            None => return Err(machine::VMError::StackEnded),
+        };
+
+        let result = self.host_mut::<T>(pointer).map(f);
+        self.vm.handle_minus(ir::Value::Host(pointer))?;
+        result
+    }
+}
+
+pub type NativeFn = fn(ctx: NativeHandlerCtx) -> Result<(), machine::VMError>;
+
+pub type NativeClosure = dyn Fn(NativeHandlerCtx) -> Result<(), machine::VMError>;
+
+pub type NativeClosureMut = dyn FnMut(NativeHandlerCtx) -> Result<(), machine::VMError>;
+
+// closures let natives keep state, like a database connection or a counter.
+// cloning a handler does not clone the closure, all clones share it and its state
+#[derive(Clone)]
+pub enum NativeHandler {
+    Fn(NativeFn),
+    Closure(Rc<NativeClosure>),
+    ClosureMut(Rc<RefCell<Box<NativeClosureMut>>>),
+}
+
+impl NativeHandler {
+    pub fn call(&self, ctx: NativeHandlerCtx) -> Result<(), machine::VMError> {
+        match self {
+            NativeHandler::Fn(f) => f(ctx),
+            NativeHandler::Closure(f) => f(ctx),
+            // a `FnMut` native cannot run again until it returns
+            NativeHandler::ClosureMut(f) => match f.try_borrow_mut() {
+                Ok(mut f) => f(ctx),
+                Err(_) => Err(machine::VMError::NativeAlreadyRunning),
+            },
+        }
+    }
+}
 
 pub struct NativeGroup {
     pub natives: HashMap<LocalStr, NativeHandler>,
@@ -32,12 +127,33 @@ impl NativeGroup {
 
     pub fn add_child(&mut self, group: &NativeGroup) -> Result<(), ir::IRError> {
         for (k, v) in &group.natives {
-            self.add_native(Self::merge_prefix(&group.prefix, k).as_str(), *v)?;
+            self.add_handler(Self::merge_prefix(&group.prefix, k).as_str(), v.clone())?;
         }
         Ok(())
     }
 
-    pub fn add_native(&mut self, name: &str, f: NativeHandler) -> Result<(), ir::IRError> {
+    pub fn add_native(&mut self, name: &str, f: NativeFn) -> Result<(), ir::IRError> {
+        self.add_handler(name, NativeHandler::Fn(f))
+    }
+
+    pub fn add_closure<F>(&mut self, name: &str, f: F) -> Result<(), ir::IRError>
+    where
+        F: Fn(NativeHandlerCtx) -> Result<(), machine::VMError> + 'static,
+    {
+        self.add_handler(name, NativeHandler::Closure(Rc::new(f)))
+    }
+
+    pub fn add_closure_mut<F>(&mut self, name: &str, f: F) -> Result<(), ir::IRError>
+    where
+        F: FnMut(NativeHandlerCtx) -> Result<(), machine::VMError> + 'static,
+    {
+        self.add_handler(
+            name,
+            NativeHandler::ClosureMut(Rc::new(RefCell::new(Box::new(f)))),
+        )
+    }
+
+    pub fn add_handler(&mut self, name: &str, f: NativeHandler) -> Result<(), ir::IRError> {
         if self.natives.contains_key(name) {
             return Err(ir::IRError::BlockAlreadyExists(name.to_local_str()));
         }
@@ -117,9 +233,22 @@ create_native_group_container! {
     "",
     &vm::group(),
     &io::group(),
+    &file::group(),
     &core::group(),
+    &math::group(),
     &types::group(),
     &exceptions::group(),
     &strings::group(),
-    &os::group()
+    &regex::group(),
+    &os::group(),
+    &array::group(),
+    &bytes::group(),
+    &map::group(),
+    &time::group(),
+    &json::group(),
+    &net::group(),
+    &http::group(),
+    &hash::group(),
+    &url::group(),
+    &term::group()
 }
@@ -0,0 +1,204 @@
+use crate::{
+    define_native_group,
+    heap::{self, HeapObject},
+    machine, native,
+    resources::{Listener, Socket},
+};
+use enalang_ir as ir;
+use flexstr::{local_fmt, ToLocalStr};
+use std::{io, net::TcpStream, time::Duration};
+
+pub(crate) fn net_error(context: &str, err: io::Error) -> machine::VMError {
+    let message = match err.kind() {
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => local_fmt!("{context}: timed out"),
+        _ => local_fmt!("{context}: {err}"),
+    };
+    machine::VMError::RuntimeException(ir::Value::String(message))
+}
+
+pub(crate) fn socket(
+    vm: &mut machine::VM,
+    pointer: usize,
+) -> Result<&mut Socket, machine::VMError> {
+    match vm.heap.get_object_mut(pointer) {
+        Some(HeapObject::Socket(socket)) => Ok(socket),
+        Some(_) => Err(machine::VMError::ExpectedSocket),
+        None => Err(machine::VMError::BadPointer(pointer)),
+    }
+}
+
+fn listener(vm: &mut machine::VM, pointer: usize) -> Result<&mut Listener, machine::VMError> {
+    match vm.heap.get_object_mut(pointer) {
+        Some(HeapObject::Listener(listener)) => Ok(listener),
+        Some(_) => Err(machine::VMError::ExpectedListener),
+        None => Err(machine::VMError::BadPointer(pointer)),
+    }
+}
+
+pub(crate) fn with_handle<F>(ctx: native::NativeHandlerCtx, f: F) -> Result<(), machine::VMError>
+where
+    F: FnOnce(&mut machine::VM, usize) -> Result<(), machine::VMError>,
+{
+    let pointer = match ctx.vm.stack.pop() {
+        Some(ir::Value::Handle(pointer)) => pointer,
+        Some(other) => {
+            ctx.vm.handle_minus(other)?;
+            return Err(machine::VMError::ExpectedSocket);
+        }
+        None => return Err(machine::VMError::StackEnded),
+    };
+
+    let result = f(ctx.vm, pointer);
+    ctx.vm.handle_minus(ir::Value::Handle(pointer))?;
+    result
+}
+
+fn push_object(vm: &mut machine::VM, object: HeapObject) -> Result<(), machine::VMError> {
+    let pointer = heap::heap_result_into_vm(vm.heap.alloc_object(object))?;
+    vm.stack.push(ir::Value::Handle(pointer));
+    Ok(())
+}
+
+pub fn listen(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let address = ctx.vm.pop_string()?;
+    let listener = Listener::bind(&address).map_err(|x| net_error(&address, x))?;
+    push_object(ctx.vm, HeapObject::Listener(listener))
+}
+
+pub fn accept(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_handle(ctx, |vm, pointer| {
+        let socket = listener(vm, pointer)?
+            .accept()
+            .map_err(|x| net_error("accept", x))?;
+        push_object(vm, HeapObject::Socket(socket))
+    })
+}
+
+pub fn connect(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let address = ctx.vm.pop_string()?;
+    let socket = TcpStream::connect(address.as_str())
+        .and_then(Socket::new)
+        .map_err(|x| net_error(&address, x))?;
+    push_object(ctx.vm, HeapObject::Socket(socket))
+}
+
+pub fn read_line(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_handle(ctx, |vm, pointer| {
+        match socket(vm, pointer)?
+            .read_line()
+            .map_err(|x| net_error("read", x))?
+        {
+            Some(line) => vm.push(ir::Value::String(line.to_local_str())),
+            None => vm.push(ir::Value::Null),
+        }
+    })
+}
+
+pub fn read(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_handle(ctx, |vm, pointer| {
+        let count = vm.pop_pointer()?;
+        let data = socket(vm, pointer)?
+            .read(count)
+            .map_err(|x| net_error("read", x))?;
+        vm.push(ir::Value::Bytes(data))
+    })
+}
+
+pub fn read_all(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_handle(ctx, |vm, pointer| {
+        let data = socket(vm, pointer)?
+            .read_all()
+            .map_err(|x| net_error("read", x))?;
+        vm.push(ir::Value::Bytes(data))
+    })
+}
+
+pub fn write(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_handle(ctx, |vm, pointer| {
+        let data = vm.pop_contents()?;
+        socket(vm, pointer)?
+            .write(&data)
+            .map_err(|x| net_error("write", x))
+    })
+}
+
+pub fn set_timeout(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_handle(ctx, |vm, pointer| {
+        let ms = vm.pop_number()?.as_f64();
+        // zero disables the timeout
+        let timeout = if ms > 0.0 {
+            Some(Duration::try_from_secs_f64(ms / 1000.0).map_err(|_| {
+                machine::VMError::RuntimeException(ir::Value::String(local_fmt!(
+                    "set_timeout: invalid duration `{ms}`"
+                )))
+            })?)
+        } else {
+            None
+        };
+
+        match vm.heap.get_object_mut(pointer) {
+            Some(HeapObject::Listener(listener)) => {
+                listener.set_timeout(timeout);
+                Ok(())
+            }
+            Some(HeapObject::Socket(socket)) => socket
+                .set_timeout(timeout)
+                .map_err(|x| net_error("set_timeout", x)),
+            Some(_) => Err(machine::VMError::ExpectedSocket),
+            None => Err(machine::VMError::BadPointer(pointer)),
+        }
+    })
+}
+
+pub fn local_addr(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_handle(ctx, |vm, pointer| {
+        let address = match vm.heap.get_object_mut(pointer) {
+            Some(HeapObject::Listener(listener)) => listener.local_addr(),
+            Some(HeapObject::Socket(socket)) => socket.local_addr(),
+            Some(_) => return Err(machine::VMError::ExpectedSocket),
+            None => return Err(machine::VMError::BadPointer(pointer)),
+        }
+        .map_err(|x| net_error("local_addr", x))?;
+        vm.push(ir::Value::String(address.to_string().to_local_str()))
+    })
+}
+
+pub fn peer_addr(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_handle(ctx, |vm, pointer| {
+        let address = socket(vm, pointer)?
+            .peer_addr()
+            .map_err(|x| net_error("peer_addr", x))?;
+        vm.push(ir::Value::String(address.to_string().to_local_str()))
+    })
+}
+
+pub fn close(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    with_handle(ctx, |vm, pointer| match vm.heap.get_object_mut(pointer) {
+        Some(HeapObject::Listener(listener)) => {
+            listener.close();
+            Ok(())
+        }
+        Some(HeapObject::Socket(socket)) => {
+            socket.close();
+            Ok(())
+        }
+        Some(_) => Err(machine::VMError::ExpectedSocket),
+        None => Err(machine::VMError::BadPointer(pointer)),
+    })
+}
+
+define_native_group! {
+    group,
+    "net",
+    "listen" => listen,
+    "accept" => accept,
+    "connect" => connect,
+    "read_line" => read_line,
+    "read" => read,
+    "read_all" => read_all,
+    "write" => write,
+    "set_timeout" => set_timeout,
+    "local_addr" => local_addr,
+    "peer_addr" => peer_addr,
+    "close" => close
+}
@@ -1,7 +1,18 @@
-use crate::{define_native_group, machine, native};
+use crate::{
+    collections, define_native_group,
+    heap::{self, HeapObject},
+    machine, native,
+};
 use enalang_ir as ir;
-use flexstr::ToLocalStr;
-use std::env;
+use flexstr::{local_fmt, local_str, LocalStr, ToLocalStr};
+use num_traits::ToPrimitive;
+use std::{
+    env,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread,
+};
 
 pub fn vm_get_env(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     if let ir::Value::String(env_name) = ctx.vm.pop()? {
@@ -14,8 +25,274 @@ pub fn vm_get_env(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError>
     }
 }
 
+fn env_error(message: &str) -> machine::VMError {
+    machine::VMError::RuntimeException(ir::Value::String(message.to_local_str()))
+}
+
+pub fn vm_set_env(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let name = ctx.vm.pop_string()?;
+    let value = ctx.vm.pop_string()?;
+    // `env::set_var` panics on these instead of returning an error
+    if name.is_empty() || name.contains(['=', '\0']) || value.contains('\0') {
+        return Err(env_error(&format!("invalid environment variable `{name}`")));
+    }
+
+    env::set_var(name.as_str(), value.as_str());
+    Ok(())
+}
+
+pub fn vm_unset_env(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let name = ctx.vm.pop_string()?;
+    if name.is_empty() || name.contains(['=', '\0']) {
+        return Err(env_error(&format!("invalid environment variable `{name}`")));
+    }
+
+    env::remove_var(name.as_str());
+    Ok(())
+}
+
+pub fn vm_env(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let mut total = 0;
+    for (name, value) in env::vars_os() {
+        ctx.vm
+            .push(ir::Value::String(name.to_string_lossy().to_local_str()))?;
+        ctx.vm
+            .push(ir::Value::String(value.to_string_lossy().to_local_str()))?;
+        total += 1;
+    }
+
+    ctx.vm.push(ir::Value::Integer(total))
+}
+
+fn push_path(vm: &mut machine::VM, path: Option<PathBuf>) -> Result<(), machine::VMError> {
+    match path {
+        Some(path) => vm.push(ir::Value::String(path.to_string_lossy().to_local_str())),
+        None => vm.push(ir::Value::Null),
+    }
+}
+
+pub fn home_dir(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let home = env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" })
+        .filter(|x| !x.is_empty())
+        .map(PathBuf::from);
+    push_path(ctx.vm, home)
+}
+
+pub fn temp_dir(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    push_path(ctx.vm, Some(env::temp_dir()))
+}
+
+pub fn executable_path(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let path = env::current_exe().map_err(|x| env_error(&x.to_string()))?;
+    push_path(ctx.vm, Some(path))
+}
+
+// looks the program up in the directories of PATH, like a shell would
+pub fn find_executable(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let name = ctx.vm.pop_string()?;
+    let names: Vec<String> = if cfg!(windows) {
+        vec![name.to_string(), format!("{name}.exe")]
+    } else {
+        vec![name.to_string()]
+    };
+
+    let found = env::var_os("PATH").and_then(|paths| {
+        env::split_paths(&paths)
+            .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+            .find(|path| path.is_file())
+    });
+    push_path(ctx.vm, found)
+}
+
+pub fn args(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let args = ctx.vm.args.clone();
+    for arg in &args {
+        ctx.vm.push(ir::Value::String(arg.clone()))?;
+    }
+
+    ctx.vm.push(ir::Value::Integer(args.len() as i64))
+}
+
+pub fn exit(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let code = ctx.vm.pop_integer()?;
+    Err(machine::VMError::Exit(code.to_i32().unwrap_or(1)))
+}
+
+fn string(value: &ir::Value) -> Result<LocalStr, machine::VMError> {
+    match value {
+        ir::Value::String(st) => Ok(st.clone()),
+        _ => Err(machine::VMError::ExpectedString),
+    }
+}
+
+fn pop_args(vm: &mut machine::VM) -> Result<Vec<LocalStr>, machine::VMError> {
+    let pointer = match vm.stack.pop() {
+        Some(ir::Value::Array(pointer)) => pointer,
+        Some(other) => {
+            vm.handle_minus(other)?;
+            return Err(machine::VMError::ExpectedArray);
+        }
+        None => return Err(machine::VMError::StackEnded),
+    };
+
+    let args = match vm.heap.get_object(pointer) {
+        Some(HeapObject::Array(values)) => values.iter().map(string).collect(),
+        _ => Err(machine::VMError::BadPointer(pointer)),
+    };
+    vm.handle_minus(ir::Value::Array(pointer))?;
+    args
+}
+
+#[derive(Default)]
+struct ExecOptions {
+    cwd: Option<LocalStr>,
+    env: Vec<(LocalStr, LocalStr)>,
+    stdin: Option<Vec<u8>>,
+}
+
+fn read_options(
+    heap: &heap::Heap,
+    map: &collections::Map,
+) -> Result<ExecOptions, machine::VMError> {
+    let mut options = ExecOptions::default();
+
+    if let Some(cwd) = map.get(&ir::Value::String(local_str!("cwd")))? {
+        options.cwd = Some(string(cwd)?);
+    }
+
+    match map.get(&ir::Value::String(local_str!("env")))? {
+        Some(ir::Value::Map(pointer)) => match heap.get_object(*pointer) {
+            Some(HeapObject::Map(env)) => {
+                for (key, value) in env.iter() {
+                    options.env.push((string(key)?, string(value)?));
+                }
+            }
+            _ => return Err(machine::VMError::BadPointer(*pointer)),
+        },
+        Some(_) => return Err(machine::VMError::ExpectedMap),
+        None => {}
+    }
+
+    match map.get(&ir::Value::String(local_str!("stdin")))? {
+        Some(ir::Value::String(st)) => options.stdin = Some(st.as_bytes().to_vec()),
+        Some(ir::Value::Bytes(bytes)) => options.stdin = Some(bytes.clone()),
+        Some(_) => return Err(machine::VMError::ExpectedString),
+        None => {}
+    }
+
+    Ok(options)
+}
+
+fn pop_options(vm: &mut machine::VM) -> Result<ExecOptions, machine::VMError> {
+    let pointer = match vm.stack.pop() {
+        Some(ir::Value::Map(pointer)) => pointer,
+        Some(other) => {
+            vm.handle_minus(other)?;
+            return Err(machine::VMError::ExpectedMap);
+        }
+        None => return Err(machine::VMError::StackEnded),
+    };
+
+    let options = match vm.heap.get_object(pointer) {
+        Some(HeapObject::Map(map)) => read_options(&vm.heap, map),
+        _ => Err(machine::VMError::BadPointer(pointer)),
+    };
+    vm.handle_minus(ir::Value::Map(pointer))?;
+    options
+}
+
+// runs the program to completion and pushes a map with its status, stdout and stderr
+fn run_program(
+    vm: &mut machine::VM,
+    program: LocalStr,
+    args: Vec<LocalStr>,
+    options: ExecOptions,
+) -> Result<(), machine::VMError> {
+    if !vm.options.allow_subprocess {
+        return Err(machine::VMError::SubprocessNotAllowed);
+    }
+
+    let spawn_error = |err: std::io::Error| {
+        machine::VMError::RuntimeException(ir::Value::String(local_fmt!("{program}: {err}")))
+    };
+
+    let mut command = Command::new(program.as_str());
+    command
+        .args(args.iter().map(|x| x.as_str()))
+        .envs(options.env.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+        .stdin(match options.stdin {
+            Some(_) => Stdio::piped(),
+            None => Stdio::null(),
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(cwd) = &options.cwd {
+        command.current_dir(cwd.as_str());
+    }
+
+    let mut child = command.spawn().map_err(spawn_error)?;
+    // stdin is written from another thread, so a child filling its stdout cannot block us
+    let writer = match (child.stdin.take(), options.stdin) {
+        (Some(mut stdin), Some(input)) => Some(thread::spawn(move || stdin.write_all(&input))),
+        _ => None,
+    };
+    let output = child.wait_with_output().map_err(spawn_error)?;
+    if let Some(writer) = writer {
+        // the child may exit without reading all of its input, which is not an error
+        let _ = writer.join();
+    }
+
+    let status = match output.status.code() {
+        Some(code) => ir::Value::Integer(code as i64),
+        None => ir::Value::Null,
+    };
+    let entries = [
+        ("status", status),
+        (
+            "stdout",
+            ir::Value::String(String::from_utf8_lossy(&output.stdout).to_local_str()),
+        ),
+        (
+            "stderr",
+            ir::Value::String(String::from_utf8_lossy(&output.stderr).to_local_str()),
+        ),
+    ];
+
+    let mut result = collections::Map::new();
+    for (key, value) in entries {
+        result.insert(ir::Value::String(key.to_local_str()), value)?;
+    }
+    let pointer = heap::heap_result_into_vm(vm.heap.alloc_object(HeapObject::Map(result)))?;
+    vm.stack.push(ir::Value::Map(pointer));
+    Ok(())
+}
+
+pub fn exec(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let program = ctx.vm.pop_string()?;
+    let args = pop_args(ctx.vm)?;
+    run_program(ctx.vm, program, args, ExecOptions::default())
+}
+
+pub fn exec_with(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let program = ctx.vm.pop_string()?;
+    let args = pop_args(ctx.vm)?;
+    let options = pop_options(ctx.vm)?;
+    run_program(ctx.vm, program, args, options)
+}
+
 define_native_group! {
     group,
     "ena.vm.os",
-    "get_env" => vm_get_env
+    "get_env" => vm_get_env,
+    "set_env" => vm_set_env,
+    "unset_env" => vm_unset_env,
+    "env" => vm_env,
+    "home_dir" => home_dir,
+    "temp_dir" => temp_dir,
+    "executable_path" => executable_path,
+    "find_executable" => find_executable,
+    "args" => args,
+    "exit" => exit,
+    "exec" => exec,
+    "exec_with" => exec_with
 }
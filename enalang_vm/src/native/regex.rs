@@ -0,0 +1,81 @@
+use crate::{define_native_group, machine, native};
+use enalang_ir as ir;
+use flexstr::{local_fmt, LocalStr, ToLocalStr};
+
+const CACHE_LIMIT: usize = 256;
+
+fn compile(vm: &mut machine::VM, pattern: LocalStr) -> Result<::regex::Regex, machine::VMError> {
+    if let Some(re) = vm.regex_cache.get(&pattern) {
+        return Ok(re.clone());
+    }
+
+    let re = ::regex::Regex::new(pattern.as_str())
+        .map_err(|e| machine::VMError::InvalidRegex(local_fmt!("{e}")))?;
+
+    // patterns built at runtime could grow the cache forever
+    if vm.regex_cache.len() >= CACHE_LIMIT {
+        vm.regex_cache.clear();
+    }
+    vm.regex_cache.insert(pattern, re.clone());
+
+    Ok(re)
+}
+
+pub fn is_match(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let text = ctx.vm.pop_string()?;
+    let pattern = ctx.vm.pop_string()?;
+    let re = compile(ctx.vm, pattern)?;
+    ctx.vm.push(ir::Value::Boolean(re.is_match(text.as_str())))
+}
+
+pub fn find(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let text = ctx.vm.pop_string()?;
+    let pattern = ctx.vm.pop_string()?;
+    let re = compile(ctx.vm, pattern)?;
+
+    match re.captures(text.as_str()) {
+        Some(captures) => {
+            for group in captures.iter() {
+                match group {
+                    Some(m) => ctx.vm.push(ir::Value::String(m.as_str().to_local_str()))?,
+                    None => ctx.vm.push(ir::Value::Null)?,
+                }
+            }
+            ctx.vm.push(ir::Value::Integer(captures.len() as i64))
+        }
+        None => ctx.vm.push(ir::Value::Integer(0)),
+    }
+}
+
+pub fn find_all(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let text = ctx.vm.pop_string()?;
+    let pattern = ctx.vm.pop_string()?;
+    let re = compile(ctx.vm, pattern)?;
+
+    let mut total = 0;
+    for m in re.find_iter(text.as_str()) {
+        ctx.vm.push(ir::Value::String(m.as_str().to_local_str()))?;
+        total += 1;
+    }
+
+    ctx.vm.push(ir::Value::Integer(total))
+}
+
+pub fn replace(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let text = ctx.vm.pop_string()?;
+    let pattern = ctx.vm.pop_string()?;
+    let template = ctx.vm.pop_string()?;
+    let re = compile(ctx.vm, pattern)?;
+
+    let result = re.replace_all(text.as_str(), template.as_str());
+    ctx.vm.push(ir::Value::String(result.to_local_str()))
+}
+
+define_native_group! {
+    group,
+    "regex",
+    "is_match" => is_match,
+    "find" => find,
+    "find_all" => find_all,
+    "replace" => replace
+}
@@ -1,10 +1,10 @@
-use crate::{define_native_group, machine, native};
+use crate::{define_native_group, machine, native, native::types};
 use enalang_ir as ir;
-use flexstr::{local_fmt, ToLocalStr};
+use flexstr::{local_fmt, local_str, ToLocalStr};
 
 pub fn strlen(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     if let ir::Value::String(st) = ctx.vm.pop()? {
-        ctx.vm.push(ir::Value::Number(st.len() as f64))
+        ctx.vm.push(ir::Value::Integer(st.len() as i64))
     } else {
         Err(machine::VMError::ExpectedString)
     }
@@ -26,7 +26,7 @@ pub fn split(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
             ctx.vm.push(ir::Value::String(val.to_local_str()))?;
         }
 
-        ctx.vm.push(ir::Value::Number(vals.len() as f64))?;
+        ctx.vm.push(ir::Value::Integer(vals.len() as i64))?;
 
         Ok(())
     } else {
@@ -51,7 +51,7 @@ pub fn chars(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
             ctx.vm.push(ir::Value::String(ch.to_local_str()))?;
         }
 
-        ctx.vm.push(ir::Value::Number(chars.len() as f64))?;
+        ctx.vm.push(ir::Value::Integer(chars.len() as i64))?;
 
         Ok(())
     } else {
@@ -59,6 +59,211 @@ pub fn chars(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     }
 }
 
+// converts a char index into a byte offset, allowing the index right after the last char
+fn byte_offset(st: &str, index: usize) -> Option<usize> {
+    st.char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(st.len()))
+        .nth(index)
+}
+
+// stringifies the top `count` values in stack order and pops them
+fn pop_stringified(vm: &mut machine::VM, count: usize) -> Result<Vec<String>, machine::VMError> {
+    if count > vm.stack.len() {
+        return Err(machine::VMError::StackEnded);
+    }
+
+    let values: Vec<String> = vm.stack[vm.stack.len() - count..]
+        .iter()
+        .map(|x| types::stringify(&vm.heap, x).to_string())
+        .collect();
+
+    for _ in 0..count {
+        vm.pop()?;
+    }
+
+    Ok(values)
+}
+
+pub fn char_count(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    ctx.vm.push(ir::Value::Integer(st.chars().count() as i64))
+}
+
+pub fn slice(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    let start = ctx.vm.pop_pointer()?;
+    let end = ctx.vm.pop_pointer()?;
+
+    let end_offset = byte_offset(&st, end).ok_or(machine::VMError::IndexOutOfBounds(end))?;
+    let start_offset = match byte_offset(&st, start) {
+        Some(offset) if start <= end => offset,
+        _ => return Err(machine::VMError::IndexOutOfBounds(start)),
+    };
+
+    ctx.vm.push(ir::Value::String(
+        st[start_offset..end_offset].to_local_str(),
+    ))
+}
+
+pub fn index_of(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    let needle = ctx.vm.pop_string()?;
+
+    match st.find(needle.as_str()) {
+        Some(i) => ctx
+            .vm
+            .push(ir::Value::Integer(st[..i].chars().count() as i64)),
+        None => ctx.vm.push(ir::Value::Null),
+    }
+}
+
+pub fn replace(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    let from = ctx.vm.pop_string()?;
+    let to = ctx.vm.pop_string()?;
+    ctx.vm.push(ir::Value::String(
+        st.replace(from.as_str(), to.as_str()).to_local_str(),
+    ))
+}
+
+pub fn trim(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    ctx.vm.push(ir::Value::String(st.trim().to_local_str()))
+}
+
+pub fn trim_start(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    ctx.vm
+        .push(ir::Value::String(st.trim_start().to_local_str()))
+}
+
+pub fn trim_end(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    ctx.vm.push(ir::Value::String(st.trim_end().to_local_str()))
+}
+
+pub fn upper(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    ctx.vm
+        .push(ir::Value::String(st.to_uppercase().to_local_str()))
+}
+
+pub fn lower(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    ctx.vm
+        .push(ir::Value::String(st.to_lowercase().to_local_str()))
+}
+
+pub fn starts_with(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    let prefix = ctx.vm.pop_string()?;
+    ctx.vm
+        .push(ir::Value::Boolean(st.starts_with(prefix.as_str())))
+}
+
+pub fn ends_with(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    let suffix = ctx.vm.pop_string()?;
+    ctx.vm
+        .push(ir::Value::Boolean(st.ends_with(suffix.as_str())))
+}
+
+// strings built from a count on the stack are limited, so a bad count raises an
+// exception instead of exhausting the memory of the process
+const MAX_STRING_LEN: usize = 1 << 26;
+
+fn check_len(len: Option<usize>) -> Result<(), machine::VMError> {
+    match len {
+        Some(len) if len <= MAX_STRING_LEN => Ok(()),
+        _ => Err(machine::VMError::RuntimeException(ir::Value::String(
+            local_str!("string is too long"),
+        ))),
+    }
+}
+
+pub fn repeat(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    let times = ctx.vm.pop_pointer()?;
+    check_len(st.len().checked_mul(times))?;
+    ctx.vm
+        .push(ir::Value::String(st.repeat(times).to_local_str()))
+}
+
+pub fn join(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let separator = ctx.vm.pop_string()?;
+    let count = ctx.vm.pop_pointer()?;
+    let values = pop_stringified(ctx.vm, count)?;
+    ctx.vm.push(ir::Value::String(
+        values.join(separator.as_str()).to_local_str(),
+    ))
+}
+
+fn pad(ctx: native::NativeHandlerCtx, start: bool) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    let width = ctx.vm.pop_pointer()?;
+    let fill = ctx.vm.pop_string()?;
+
+    let missing = width.saturating_sub(st.chars().count());
+    let fill_char_len = fill.chars().map(char::len_utf8).max().unwrap_or(0);
+    check_len(
+        missing
+            .checked_mul(fill_char_len)
+            .and_then(|x| x.checked_add(st.len())),
+    )?;
+    let padding: String = fill.chars().cycle().take(missing).collect();
+
+    let result = if start {
+        local_fmt!("{padding}{st}")
+    } else {
+        local_fmt!("{st}{padding}")
+    };
+    ctx.vm.push(ir::Value::String(result))
+}
+
+pub fn pad_start(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    pad(ctx, true)
+}
+
+pub fn pad_end(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    pad(ctx, false)
+}
+
+// fills `{}` placeholders with values from the stack. `{{` and `}}` produce literal braces
+pub fn format(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let fmt = ctx.vm.pop_string()?;
+
+    let mut pieces: Vec<Option<String>> = vec![Some(String::new())];
+    let mut chars = fmt.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('{', Some('}')) => {
+                chars.next();
+                pieces.push(None);
+                pieces.push(Some(String::new()));
+                continue;
+            }
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+            }
+            _ => {}
+        }
+
+        if let Some(Some(literal)) = pieces.last_mut() {
+            literal.push(ch);
+        }
+    }
+
+    let count = pieces.iter().filter(|x| x.is_none()).count();
+    let mut values = pop_stringified(ctx.vm, count)?.into_iter();
+
+    let result: String = pieces
+        .into_iter()
+        .map(|x| x.unwrap_or_else(|| values.next().unwrap_or_default()))
+        .collect();
+    ctx.vm.push(ir::Value::String(result.to_local_str()))
+}
+
 define_native_group! {
     group,
     "string",
@@ -66,5 +271,21 @@ define_native_group! {
     "concat" => concat,
     "split" => split,
     "contains" => contains,
-    "chars" => chars
+    "chars" => chars,
+    "char_count" => char_count,
+    "slice" => slice,
+    "index_of" => index_of,
+    "replace" => replace,
+    "trim" => trim,
+    "trim_start" => trim_start,
+    "trim_end" => trim_end,
+    "upper" => upper,
+    "lower" => lower,
+    "starts_with" => starts_with,
+    "ends_with" => ends_with,
+    "repeat" => repeat,
+    "join" => join,
+    "pad_start" => pad_start,
+    "pad_end" => pad_end,
+    "format" => format
 }
@@ -0,0 +1,212 @@
+use crate::{define_native_group, machine, native};
+use crossterm::{
+    cursor,
+    style::{Attribute, Color, Stylize},
+    terminal, Command, ExecutableCommand,
+};
+use enalang_ir as ir;
+use flexstr::{local_fmt, ToLocalStr};
+use num_traits::ToPrimitive;
+use std::io;
+
+fn term_error(err: io::Error) -> machine::VMError {
+    machine::VMError::RuntimeException(ir::Value::String(local_fmt!("term: {err}")))
+}
+
+fn invalid(what: &str, name: &str) -> machine::VMError {
+    machine::VMError::RuntimeException(ir::Value::String(local_fmt!(
+        "term: unknown {what} `{name}`"
+    )))
+}
+
+// colors are names like `red` and `dark_blue`, `#rrggbb` or an ansi color number
+fn pop_color(vm: &mut machine::VM) -> Result<Color, machine::VMError> {
+    match vm.pop()? {
+        ir::Value::Integer(i) => u8::try_from(i)
+            .map(Color::AnsiValue)
+            .map_err(|_| invalid("color", &i.to_string())),
+        ir::Value::String(name) => {
+            if let Some(hex) = name.strip_prefix('#') {
+                let rgb = hex::decode(hex).map_err(|_| invalid("color", &name))?;
+                return match rgb[..] {
+                    [r, g, b] => Ok(Color::Rgb { r, g, b }),
+                    _ => Err(invalid("color", &name)),
+                };
+            }
+
+            Ok(match name.as_str() {
+                "black" => Color::Black,
+                "red" => Color::Red,
+                "green" => Color::Green,
+                "yellow" => Color::Yellow,
+                "blue" => Color::Blue,
+                "magenta" => Color::Magenta,
+                "cyan" => Color::Cyan,
+                "white" => Color::White,
+                "grey" | "gray" => Color::Grey,
+                "dark_red" => Color::DarkRed,
+                "dark_green" => Color::DarkGreen,
+                "dark_yellow" => Color::DarkYellow,
+                "dark_blue" => Color::DarkBlue,
+                "dark_magenta" => Color::DarkMagenta,
+                "dark_cyan" => Color::DarkCyan,
+                "dark_grey" | "dark_gray" => Color::DarkGrey,
+                _ => return Err(invalid("color", &name)),
+            })
+        }
+        _ => Err(machine::VMError::ExpectedString),
+    }
+}
+
+// commands are written to the stdout of the vm, so they can be captured like other output
+fn run(vm: &machine::VM, command: impl Command) -> Result<(), machine::VMError> {
+    vm.io.stdout().execute(command).map_err(term_error)?;
+    Ok(())
+}
+
+fn pop_count(vm: &mut machine::VM) -> Result<u16, machine::VMError> {
+    let n = vm.pop_integer()?;
+    n.to_u16().ok_or(machine::VMError::ExpectedInteger)
+}
+
+fn push_styled(
+    vm: &mut machine::VM,
+    styled: impl std::fmt::Display,
+) -> Result<(), machine::VMError> {
+    vm.push(ir::Value::String(styled.to_string().to_local_str()))
+}
+
+pub fn color(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let text = ctx.vm.pop_string()?;
+    let color = pop_color(ctx.vm)?;
+    push_styled(ctx.vm, text.as_str().with(color))
+}
+
+pub fn background(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let text = ctx.vm.pop_string()?;
+    let color = pop_color(ctx.vm)?;
+    push_styled(ctx.vm, text.as_str().on(color))
+}
+
+pub fn style(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let text = ctx.vm.pop_string()?;
+    let name = ctx.vm.pop_string()?;
+    let attribute = match name.as_str() {
+        "bold" => Attribute::Bold,
+        "dim" => Attribute::Dim,
+        "italic" => Attribute::Italic,
+        "underline" => Attribute::Underlined,
+        "blink" => Attribute::SlowBlink,
+        "reverse" => Attribute::Reverse,
+        "hidden" => Attribute::Hidden,
+        "strikethrough" => Attribute::CrossedOut,
+        _ => return Err(invalid("style", &name)),
+    };
+    push_styled(ctx.vm, text.as_str().attribute(attribute))
+}
+
+pub fn move_to(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let column = pop_count(ctx.vm)?;
+    let row = pop_count(ctx.vm)?;
+    run(ctx.vm, cursor::MoveTo(column, row))
+}
+
+pub fn move_up(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let n = pop_count(ctx.vm)?;
+    run(ctx.vm, cursor::MoveUp(n))
+}
+
+pub fn move_down(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let n = pop_count(ctx.vm)?;
+    run(ctx.vm, cursor::MoveDown(n))
+}
+
+pub fn move_left(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let n = pop_count(ctx.vm)?;
+    run(ctx.vm, cursor::MoveLeft(n))
+}
+
+pub fn move_right(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let n = pop_count(ctx.vm)?;
+    run(ctx.vm, cursor::MoveRight(n))
+}
+
+pub fn hide_cursor(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    run(ctx.vm, cursor::Hide)
+}
+
+pub fn show_cursor(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    run(ctx.vm, cursor::Show)
+}
+
+pub fn clear(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    run(ctx.vm, terminal::Clear(terminal::ClearType::All))?;
+    run(ctx.vm, cursor::MoveTo(0, 0))
+}
+
+pub fn clear_line(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    run(ctx.vm, terminal::Clear(terminal::ClearType::CurrentLine))?;
+    run(ctx.vm, cursor::MoveToColumn(0))
+}
+
+// the terminal natives below go through the io of the vm, so an embedder
+// decides which terminal, if any, they reach
+pub fn size(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let (columns, rows) = ctx.vm.io.terminal_size().map_err(term_error)?;
+    ctx.vm.push(ir::Value::Integer(columns as i64))?;
+    ctx.vm.push(ir::Value::Integer(rows as i64))
+}
+
+pub fn is_terminal(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let terminal = ctx.vm.io.is_terminal();
+    ctx.vm.push(ir::Value::Boolean(terminal))
+}
+
+pub fn raw_mode(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let enable = match ctx.vm.pop()? {
+        ir::Value::Boolean(b) => b,
+        _ => return Err(machine::VMError::ExpectedBoolean),
+    };
+    ctx.vm.io.set_raw_mode(enable).map_err(term_error)
+}
+
+pub fn alternate_screen(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let enable = match ctx.vm.pop()? {
+        ir::Value::Boolean(b) => b,
+        _ => return Err(machine::VMError::ExpectedBoolean),
+    };
+
+    if enable {
+        run(ctx.vm, terminal::EnterAlternateScreen)
+    } else {
+        run(ctx.vm, terminal::LeaveAlternateScreen)
+    }
+}
+
+// waits for a key press. use raw mode to get keys without waiting for enter
+pub fn read_key(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let name = ctx.vm.io.read_key().map_err(term_error)?;
+    ctx.vm.push(ir::Value::String(name.to_local_str()))
+}
+
+define_native_group! {
+    group,
+    "term",
+    "color" => color,
+    "background" => background,
+    "style" => style,
+    "move_to" => move_to,
+    "move_up" => move_up,
+    "move_down" => move_down,
+    "move_left" => move_left,
+    "move_right" => move_right,
+    "hide_cursor" => hide_cursor,
+    "show_cursor" => show_cursor,
+    "clear" => clear,
+    "clear_line" => clear_line,
+    "size" => size,
+    "terminal?" => is_terminal,
+    "raw_mode" => raw_mode,
+    "alternate_screen" => alternate_screen,
+    "read_key" => read_key
+}
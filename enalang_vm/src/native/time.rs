@@ -0,0 +1,249 @@
+use crate::{define_native_group, machine, native, number::Number};
+use enalang_ir as ir;
+use flexstr::{local_fmt, ToLocalStr};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// days since 1970-01-01 for a date in the proleptic gregorian calendar
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+fn format_timestamp(seconds: i64, millis: u32) -> String {
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+    let time = seconds.rem_euclid(86400);
+    let (hour, minute, second) = (time / 3600, time % 3600 / 60, time % 60);
+
+    let mut st = format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}");
+    if millis != 0 {
+        st.push_str(&format!(".{millis:03}"));
+    }
+    st.push('Z');
+    st
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).copied()
+    }
+
+    fn eat(&mut self, ch: u8) -> bool {
+        if self.peek() == Some(ch) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, ch: u8) -> Result<(), String> {
+        if self.eat(ch) {
+            Ok(())
+        } else {
+            Err(format!("expected `{}` at {}", ch as char, self.position))
+        }
+    }
+
+    fn digits(&mut self, count: usize) -> Result<i64, String> {
+        let mut value = 0;
+        for _ in 0..count {
+            match self.peek() {
+                Some(ch) if ch.is_ascii_digit() => {
+                    value = value * 10 + (ch - b'0') as i64;
+                    self.position += 1;
+                }
+                _ => return Err(format!("expected digit at {}", self.position)),
+            }
+        }
+        Ok(value)
+    }
+
+    fn in_range(&self, value: i64, min: i64, max: i64, start: usize) -> Result<i64, String> {
+        if value < min || value > max {
+            Err(format!("value out of range at {start}"))
+        } else {
+            Ok(value)
+        }
+    }
+
+    fn number(&mut self, count: usize, min: i64, max: i64) -> Result<i64, String> {
+        let start = self.position;
+        let value = self.digits(count)?;
+        self.in_range(value, min, max, start)
+    }
+}
+
+// parses `YYYY-MM-DD`, optionally followed by `THH:MM[:SS[.fff]]` and a `Z` or `+HH:MM` offset
+fn parse_timestamp(st: &str) -> Result<(i64, u32), String> {
+    let mut parser = Parser {
+        input: st.as_bytes(),
+        position: 0,
+    };
+
+    let year = parser.digits(4)?;
+    parser.expect(b'-')?;
+    let month = parser.number(2, 1, 12)? as u32;
+    parser.expect(b'-')?;
+    let start = parser.position;
+    let day = parser.digits(2)?;
+    let day = parser.in_range(day, 1, days_in_month(year, month) as i64, start)? as u32;
+
+    let mut seconds = days_from_civil(year, month, day) * 86400;
+    let mut nanos = 0;
+
+    if parser.eat(b'T') || parser.eat(b't') || parser.eat(b' ') {
+        seconds += parser.number(2, 0, 23)? * 3600;
+        parser.expect(b':')?;
+        seconds += parser.number(2, 0, 59)? * 60;
+        if parser.eat(b':') {
+            seconds += parser.number(2, 0, 59)?;
+            if parser.eat(b'.') || parser.eat(b',') {
+                let start = parser.position;
+                let mut scale = 100_000_000;
+                while let Some(ch) = parser.peek().filter(u8::is_ascii_digit) {
+                    nanos += (ch - b'0') as u32 * scale;
+                    scale /= 10;
+                    parser.position += 1;
+                }
+                if parser.position == start {
+                    return Err(format!("expected digit at {start}"));
+                }
+            }
+        }
+
+        match parser.peek() {
+            Some(b'Z') | Some(b'z') => parser.position += 1,
+            Some(sign @ (b'+' | b'-')) => {
+                parser.position += 1;
+                let mut offset = parser.number(2, 0, 23)? * 3600;
+                if parser.eat(b':') || parser.peek().filter(u8::is_ascii_digit).is_some() {
+                    offset += parser.number(2, 0, 59)? * 60;
+                }
+                seconds += if sign == b'+' { -offset } else { offset };
+            }
+            _ => {}
+        }
+    }
+
+    if parser.position != st.len() {
+        return Err(format!("unexpected character at {}", parser.position));
+    }
+
+    Ok((seconds, nanos))
+}
+
+fn push_seconds(vm: &mut machine::VM, seconds: i64, nanos: u32) -> Result<(), machine::VMError> {
+    if nanos == 0 {
+        vm.push(ir::Value::Integer(seconds))
+    } else {
+        vm.push(ir::Value::Number(seconds as f64 + nanos as f64 / 1e9))
+    }
+}
+
+fn since_epoch() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+pub fn now(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    ctx.vm
+        .push(ir::Value::Integer(since_epoch().as_secs() as i64))
+}
+
+pub fn now_ms(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    ctx.vm
+        .push(ir::Value::Integer(since_epoch().as_millis() as i64))
+}
+
+pub fn monotonic_ms(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let elapsed = ctx.vm.start_time.elapsed();
+    ctx.vm.push(ir::Value::Integer(elapsed.as_millis() as i64))
+}
+
+pub fn monotonic_ns(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let elapsed = ctx.vm.start_time.elapsed();
+    ctx.vm.push(ir::Value::Integer(elapsed.as_nanos() as i64))
+}
+
+pub fn sleep(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let ms = ctx.vm.pop_number()?.as_f64();
+    if ms > 0.0 {
+        let duration = Duration::try_from_secs_f64(ms / 1000.0).map_err(|_| {
+            machine::VMError::RuntimeException(ir::Value::String(local_fmt!(
+                "invalid duration `{ms}`"
+            )))
+        })?;
+        std::thread::sleep(duration);
+    }
+    Ok(())
+}
+
+pub fn format(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let (seconds, millis) = match ctx.vm.pop_number()? {
+        Number::Integer(seconds) => (seconds, 0),
+        number => {
+            let ms = (number.as_f64() * 1000.0).round() as i64;
+            (ms.div_euclid(1000), ms.rem_euclid(1000) as u32)
+        }
+    };
+    ctx.vm.push(ir::Value::String(
+        format_timestamp(seconds, millis).to_local_str(),
+    ))
+}
+
+pub fn parse(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = ctx.vm.pop_string()?;
+    let (seconds, nanos) = parse_timestamp(&st).map_err(|err| {
+        machine::VMError::RuntimeException(ir::Value::String(local_fmt!(
+            "invalid timestamp `{st}`: {err}"
+        )))
+    })?;
+    push_seconds(ctx.vm, seconds, nanos)
+}
+
+define_native_group! {
+    group,
+    "time",
+    "now" => now,
+    "now_ms" => now_ms,
+    "monotonic_ms" => monotonic_ms,
+    "monotonic_ns" => monotonic_ns,
+    "sleep" => sleep,
+    "format" => format,
+    "parse" => parse
+}
@@ -1,21 +1,81 @@
-use crate::{define_native_group, machine, native};
+use crate::{define_native_group, heap, machine, native};
 use enalang_ir as ir;
-use flexstr::{local_fmt, local_str};
+use flexstr::{LocalStr, ToLocalStr};
 
-pub fn into_string(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    let val = ctx.vm.pop()?;
+pub fn stringify(heap: &heap::Heap, value: &ir::Value) -> LocalStr {
+    match value {
+        ir::Value::String(st) => st.clone(),
+        _ => stringify_nested(heap, value, &mut Vec::new()).to_local_str(),
+    }
+}
+
+fn stringify_nested(heap: &heap::Heap, value: &ir::Value, seen: &mut Vec<usize>) -> String {
+    match value {
+        ir::Value::Boolean(true) => String::from("true"),
+        ir::Value::Boolean(false) => String::from("false"),
+        ir::Value::String(st) if seen.is_empty() => st.to_string(),
+        ir::Value::String(st) => format!("{st:?}"),
+        ir::Value::Bytes(bytes) => {
+            let escaped: Vec<u8> = bytes
+                .iter()
+                .flat_map(|x| std::ascii::escape_default(*x))
+                .collect();
+            format!("b\"{}\"", String::from_utf8_lossy(&escaped))
+        }
+        ir::Value::Null => String::from("null"),
+        ir::Value::Block(block_name) => format!("'{}", block_name),
+        ir::Value::Number(num) => format!("{}", num),
+        ir::Value::Integer(num) => format!("{}", num),
+        ir::Value::BigInteger(num) => format!("{}", num),
+        ir::Value::Pointer(pointer) => format!("{}->", pointer),
+        ir::Value::WeakPointer(weak) => format!("{}~>", weak),
+        ir::Value::Handle(pointer) => format!("<handle {}>", pointer),
+        ir::Value::Host(pointer) => match heap.get_object(*pointer) {
+            Some(heap::HeapObject::Host(object)) => format!("<{} {}>", object.type_name, pointer),
+            _ => format!("<host {}>", pointer),
+        },
+        ir::Value::Exception(err) => format!("{err:?}"),
+        ir::Value::Atom(atom) => format!(":{atom}"),
+        ir::Value::Array(pointer) | ir::Value::Map(pointer) if seen.contains(pointer) => {
+            String::from("...")
+        }
+        ir::Value::Array(pointer) | ir::Value::Map(pointer) => {
+            seen.push(*pointer);
+            let st = match heap.get_object(*pointer) {
+                Some(heap::HeapObject::Array(vec)) => {
+                    let items: Vec<String> = vec
+                        .iter()
+                        .map(|x| stringify_nested(heap, x, seen))
+                        .collect();
+                    format!("[{}]", items.join(", "))
+                }
+                Some(heap::HeapObject::Map(map)) => {
+                    let items: Vec<String> = map
+                        .iter()
+                        .map(|(k, v)| {
+                            format!(
+                                "{}: {}",
+                                stringify_nested(heap, k, seen),
+                                stringify_nested(heap, v, seen)
+                            )
+                        })
+                        .collect();
+                    format!("{{{}}}", items.join(", "))
+                }
+                _ => format!("{}->", pointer),
+            };
+            seen.pop();
+            st
+        }
+    }
+}
 
-    let st = match val {
-        ir::Value::Boolean(true) => local_str!("true"),
-        ir::Value::Boolean(false) => local_str!("false"),
-        ir::Value::String(st) => st,
-        ir::Value::Null => local_str!("null"),
-        ir::Value::Block(block_name) => local_fmt!("'{}", block_name),
-        ir::Value::Number(num) => local_fmt!("{}", num),
-        ir::Value::Pointer(pointer) => local_fmt!("{}->", pointer),
-        ir::Value::Exception(err) => local_fmt!("{err:?}"),
-        ir::Value::Atom(atom) => local_fmt!(":{atom}"),
+pub fn into_string(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let st = match ctx.vm.stack.last() {
+        Some(value) => stringify(&ctx.vm.heap, value),
+        None => return Err(machine::VMError::StackEnded),
     };
+    ctx.vm.pop()?;
 
     ctx.vm.push(ir::Value::String(st))
 }
@@ -23,16 +83,16 @@ pub fn into_string(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError
 pub fn into_number(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     let val = ctx.vm.pop()?;
 
-    let st: f64 = match val {
-        ir::Value::Boolean(true) => 1.0,
-        ir::Value::Boolean(false) => 0.0,
-        ir::Value::Null => -1.0,
-        ir::Value::Number(num) => num,
-        ir::Value::Pointer(pointer) => pointer as f64,
+    let num = match val {
+        ir::Value::Boolean(true) => ir::Value::Integer(1),
+        ir::Value::Boolean(false) => ir::Value::Integer(0),
+        ir::Value::Null => ir::Value::Integer(-1),
+        ir::Value::Number(_) | ir::Value::Integer(_) | ir::Value::BigInteger(_) => val,
+        ir::Value::Pointer(pointer) => ir::Value::Integer(pointer as i64),
         _ => return Err(machine::VMError::CannotConvert(val)),
     };
 
-    ctx.vm.push(ir::Value::Number(st))
+    ctx.vm.push(num)
 }
 
 pub fn is_pointer(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
@@ -40,7 +100,48 @@ pub fn is_pointer(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError>
     ctx.vm.push(ir::Value::Boolean(val))
 }
 
+pub fn is_weak_pointer(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let val = matches!(ctx.vm.pop()?, ir::Value::WeakPointer(_));
+    ctx.vm.push(ir::Value::Boolean(val))
+}
+
+pub fn is_array(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let val = matches!(ctx.vm.pop()?, ir::Value::Array(_));
+    ctx.vm.push(ir::Value::Boolean(val))
+}
+
+pub fn is_map(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let val = matches!(ctx.vm.pop()?, ir::Value::Map(_));
+    ctx.vm.push(ir::Value::Boolean(val))
+}
+
+pub fn is_handle(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let val = matches!(ctx.vm.pop()?, ir::Value::Handle(_));
+    ctx.vm.push(ir::Value::Boolean(val))
+}
+
+pub fn is_host(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let val = matches!(ctx.vm.pop()?, ir::Value::Host(_));
+    ctx.vm.push(ir::Value::Boolean(val))
+}
+
 pub fn is_number(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let val = matches!(
+        ctx.vm.pop()?,
+        ir::Value::Number(_) | ir::Value::Integer(_) | ir::Value::BigInteger(_)
+    );
+    ctx.vm.push(ir::Value::Boolean(val))
+}
+
+pub fn is_integer(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let val = matches!(
+        ctx.vm.pop()?,
+        ir::Value::Integer(_) | ir::Value::BigInteger(_)
+    );
+    ctx.vm.push(ir::Value::Boolean(val))
+}
+
+pub fn is_float(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     let val = matches!(ctx.vm.pop()?, ir::Value::Number(_));
     ctx.vm.push(ir::Value::Boolean(val))
 }
@@ -60,24 +161,33 @@ pub fn is_string(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError>
     ctx.vm.push(ir::Value::Boolean(val))
 }
 
+pub fn is_bytes(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let val = matches!(ctx.vm.pop()?, ir::Value::Bytes(_));
+    ctx.vm.push(ir::Value::Boolean(val))
+}
+
 pub fn is_null(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     let val = matches!(ctx.vm.pop()?, ir::Value::Null);
     ctx.vm.push(ir::Value::Boolean(val))
 }
 
 pub fn into_ptr(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    if let ir::Value::Number(num) = ctx.vm.pop()? {
-        let ptr = num as usize;
-
-        if num != ptr as f64 {
-            return Err(machine::VMError::BadPointer(ptr));
+    match ctx.vm.pop()? {
+        ir::Value::Integer(num) => {
+            let ptr =
+                usize::try_from(num).map_err(|_| machine::VMError::BadPointer(num as usize))?;
+            ctx.vm.push(ir::Value::Pointer(ptr))
         }
+        ir::Value::Number(num) => {
+            let ptr = num as usize;
 
-        ctx.vm.push(ir::Value::Pointer(ptr))?;
+            if num != ptr as f64 {
+                return Err(machine::VMError::BadPointer(ptr));
+            }
 
-        Ok(())
-    } else {
-        Err(machine::VMError::ExpectedNumber)
+            ctx.vm.push(ir::Value::Pointer(ptr))
+        }
+        _ => Err(machine::VMError::ExpectedNumber),
     }
 }
 
@@ -88,9 +198,17 @@ define_native_group! {
     "into_string" => into_string,
     "into_number" => into_number,
     "is_string" => is_string,
+    "is_bytes" => is_bytes,
     "is_null" => is_null,
     "is_number" => is_number,
+    "is_integer" => is_integer,
+    "is_float" => is_float,
     "is_pointer" => is_pointer,
+    "is_weak_pointer" => is_weak_pointer,
+    "is_array" => is_array,
+    "is_map" => is_map,
+    "is_handle" => is_handle,
+    "is_host" => is_host,
     "is_block" => is_block,
     "is_bool" => is_bool
 }
@@ -0,0 +1,33 @@
+use crate::{
+    machine,
+    native::{self, ena_native},
+};
+use flexstr::{LocalStr, ToLocalStr};
+use percent_encoding::{percent_decode, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+// everything except the unreserved characters of rfc 3986
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+#[ena_native]
+pub fn encode(st: LocalStr) -> String {
+    utf8_percent_encode(&st, COMPONENT).to_string()
+}
+
+#[ena_native]
+pub fn decode(st: LocalStr) -> Result<LocalStr, machine::VMError> {
+    percent_decode(st.as_bytes())
+        .decode_utf8()
+        .map(|x| x.to_local_str())
+        .map_err(|_| machine::VMError::InvalidUtf8)
+}
+
+pub fn group() -> native::NativeGroup {
+    let mut group = native::NativeGroup::new("url");
+    register_encode(&mut group).unwrap();
+    register_decode(&mut group).unwrap();
+    group
+}
@@ -1,7 +1,8 @@
-use crate::{define_native_group, machine, native};
+use crate::{define_native_group, heap::HeapObject, machine, native};
 use enalang_ir as ir;
-use flexstr::ToLocalStr;
-use rand::{self, Rng};
+use flexstr::{local_fmt, ToLocalStr};
+use num_traits::ToPrimitive;
+use rand::{self, seq::SliceRandom, Rng, SeedableRng};
 use std::{fs::OpenOptions, io::Read};
 
 pub fn vm_load(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
@@ -26,31 +27,88 @@ pub fn vm_load(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     }
 }
 
+fn stdout_error(err: std::io::Error) -> machine::VMError {
+    machine::VMError::RuntimeException(ir::Value::String(local_fmt!("stdout: {err}")))
+}
+
 pub fn vm_debug(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
     let el = match ctx.vm.stack.pop() {
         Some(i) => i,
         None => ir::Value::Null,
     };
 
-    println!("{el:?}");
+    writeln!(ctx.vm.io.stdout(), "{el:?}").map_err(stdout_error)
+}
 
+pub fn vm_get_random(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let value = ctx.vm.rng.gen_range(0.0..=1.0);
+    ctx.vm.push(ir::Value::Number(value))?;
     Ok(())
 }
 
-pub fn vm_get_random(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    ctx.vm
-        .push(ir::Value::Number(rand::thread_rng().gen_range(0.0..=1.0)))?;
+pub fn vm_seed_random(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let seed = ctx.vm.pop_integer()?;
+    // negative seeds wrap around, so every integer is a valid seed
+    let seed = seed
+        .to_i64()
+        .map(|x| x as u64)
+        .or_else(|| seed.to_u64())
+        .ok_or(machine::VMError::ExpectedInteger)?;
+    ctx.vm.rng = rand::rngs::StdRng::seed_from_u64(seed);
     Ok(())
 }
 
+pub fn vm_random_int(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let min = ctx.vm.pop_integer()?;
+    let max = ctx.vm.pop_integer()?;
+    if min > max {
+        return Err(machine::VMError::EmptyRange);
+    }
+
+    let min = min.to_i64().ok_or(machine::VMError::ExpectedInteger)?;
+    let max = max.to_i64().ok_or(machine::VMError::ExpectedInteger)?;
+    let value = ctx.vm.rng.gen_range(min..=max);
+    ctx.vm.push(ir::Value::Integer(value))
+}
+
+pub fn vm_shuffle(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
+    let pointer = match ctx.vm.stack.pop() {
+        Some(ir::Value::Array(pointer)) => pointer,
+        Some(other) => {
+            ctx.vm.handle_minus(other)?;
+            return Err(machine::VMError::ExpectedArray);
+        }
+        None => return Err(machine::VMError::StackEnded),
+    };
+
+    let vm = &mut *ctx.vm;
+    let result = match vm.heap.get_object_mut(pointer) {
+        Some(HeapObject::Array(values)) => {
+            values.shuffle(&mut vm.rng);
+            Ok(())
+        }
+        _ => Err(machine::VMError::BadPointer(pointer)),
+    };
+    vm.handle_minus(ir::Value::Array(pointer))?;
+    result
+}
+
 pub fn vm_debug_stack(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    println!("\n=== stack debug ===\n{:?}", ctx.vm.stack);
-    Ok(())
+    writeln!(
+        ctx.vm.io.stdout(),
+        "\n=== stack debug ===\n{:?}",
+        ctx.vm.stack
+    )
+    .map_err(stdout_error)
 }
 
 pub fn vm_debug_calls(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
-    println!("\n=== call stack debug ===\n{:?}", ctx.vm.call_stack);
-    Ok(())
+    writeln!(
+        ctx.vm.io.stdout(),
+        "\n=== call stack debug ===\n{:?}",
+        ctx.vm.call_stack
+    )
+    .map_err(stdout_error)
 }
 
 pub fn vm_get_annotation(ctx: native::NativeHandlerCtx) -> Result<(), machine::VMError> {
@@ -72,6 +130,8 @@ define_native_group! {
     "debug_stack" => vm_debug_stack,
     "debug_calls" => vm_debug_calls,
     "random" => vm_get_random,
+    "seed_random" => vm_seed_random,
+    "random_int" => vm_random_int,
+    "shuffle" => vm_shuffle,
     "get_annotation" => vm_get_annotation
 }
-
@@ -0,0 +1,216 @@
+use crate::machine::VMError;
+use enalang_ir as ir;
+use flexstr::local_str;
+use ir::BigInt;
+use num_traits::{FromPrimitive, Signed, ToPrimitive, Zero};
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    Integer(i64),
+    Big(BigInt),
+    Float(f64),
+}
+
+impl Number {
+    pub fn from_value(value: &ir::Value) -> Option<Self> {
+        match value {
+            ir::Value::Integer(i) => Some(Number::Integer(*i)),
+            ir::Value::BigInteger(i) => Some(Number::Big(i.clone())),
+            ir::Value::Number(n) => Some(Number::Float(*n)),
+            _ => None,
+        }
+    }
+
+    pub fn big(value: BigInt) -> Self {
+        match i64::try_from(&value) {
+            Ok(i) => Number::Integer(i),
+            Err(_) => Number::Big(value),
+        }
+    }
+
+    pub fn into_value(self) -> ir::Value {
+        match self {
+            Number::Integer(i) => ir::Value::Integer(i),
+            Number::Big(i) => ir::Value::integer(i),
+            Number::Float(n) => ir::Value::Number(n),
+        }
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            Number::Integer(i) => *i as f64,
+            Number::Big(i) => i.to_f64().unwrap_or(f64::NAN),
+            Number::Float(n) => *n,
+        }
+    }
+
+    pub fn as_big(&self) -> Option<BigInt> {
+        match self {
+            Number::Integer(i) => Some(BigInt::from(*i)),
+            Number::Big(i) => Some(i.clone()),
+            Number::Float(_) => None,
+        }
+    }
+
+    // like `as_big`, but also accepts floats without a fractional part
+    pub fn as_integer(&self) -> Option<BigInt> {
+        match self {
+            Number::Float(n) if n.fract() == 0.0 => BigInt::from_f64(*n),
+            _ => self.as_big(),
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        !matches!(self, Number::Float(_))
+    }
+}
+
+// integers that would take more bits than this raise an exception instead of
+// exhausting the memory of the process
+pub const MAX_INTEGER_BITS: u64 = 1 << 24;
+
+pub fn too_large() -> VMError {
+    VMError::RuntimeException(ir::Value::String(local_str!("integer is too large")))
+}
+
+pub fn pair(a: &ir::Value, b: &ir::Value) -> Option<(Number, Number)> {
+    Some((Number::from_value(a)?, Number::from_value(b)?))
+}
+
+fn binary(
+    a: &Number,
+    b: &Number,
+    int: fn(i64, i64) -> Option<i64>,
+    big: fn(BigInt, BigInt) -> BigInt,
+    float: fn(f64, f64) -> f64,
+) -> Number {
+    match (a, b) {
+        (Number::Integer(x), Number::Integer(y)) => match int(*x, *y) {
+            Some(i) => Number::Integer(i),
+            None => Number::big(big(BigInt::from(*x), BigInt::from(*y))),
+        },
+        (Number::Float(_), _) | (_, Number::Float(_)) => {
+            Number::Float(float(a.as_f64(), b.as_f64()))
+        }
+        _ => Number::big(big(a.as_big().unwrap(), b.as_big().unwrap())),
+    }
+}
+
+pub fn add(a: &Number, b: &Number) -> Number {
+    binary(a, b, i64::checked_add, |x, y| x + y, |x, y| x + y)
+}
+
+pub fn sub(a: &Number, b: &Number) -> Number {
+    binary(a, b, i64::checked_sub, |x, y| x - y, |x, y| x - y)
+}
+
+pub fn mul(a: &Number, b: &Number) -> Number {
+    binary(a, b, i64::checked_mul, |x, y| x * y, |x, y| x * y)
+}
+
+// division of integers stays exact when possible and falls back to floats otherwise
+pub fn div(a: &Number, b: &Number) -> Number {
+    match (a.as_big(), b.as_big()) {
+        (Some(x), Some(y)) if !y.is_zero() && (&x % &y).is_zero() => Number::big(x / y),
+        _ => Number::Float(a.as_f64() / b.as_f64()),
+    }
+}
+
+pub fn pow(a: &Number, b: &Number) -> Result<Number, VMError> {
+    match (a.as_big(), b) {
+        (Some(x), Number::Integer(y)) if *y >= 0 => {
+            // 0, 1 and -1 stay small for any exponent
+            if x.bits() > 1 && x.bits().saturating_mul(*y as u64) > MAX_INTEGER_BITS {
+                return Err(too_large());
+            }
+            match u32::try_from(*y) {
+                Ok(y) => Ok(Number::big(x.pow(y))),
+                // only 0, 1 and -1 get here, where only the parity of the exponent matters
+                Err(_) => Ok(Number::big(x.pow(2 + (*y % 2) as u32))),
+            }
+        }
+        _ => Ok(Number::Float(a.as_f64().powf(b.as_f64()))),
+    }
+}
+
+// euclidean remainder, which is never negative
+pub fn rem(a: &Number, b: &Number) -> Result<Number, VMError> {
+    match (a, b) {
+        (Number::Float(_), _) | (_, Number::Float(_)) => {
+            Ok(Number::Float(a.as_f64().rem_euclid(b.as_f64())))
+        }
+        (Number::Integer(x), Number::Integer(y)) if *y != 0 => match x.checked_rem_euclid(*y) {
+            Some(i) => Ok(Number::Integer(i)),
+            None => Ok(Number::Integer(0)),
+        },
+        _ => match (a.as_big(), b.as_big()) {
+            (Some(x), Some(y)) if !y.is_zero() => {
+                let r = x % &y;
+                Ok(Number::big(if r.is_negative() { r + y.abs() } else { r }))
+            }
+            _ => Err(VMError::DivisionByZero),
+        },
+    }
+}
+
+// euclidean division, the counterpart of `rem`
+pub fn div_euclid(a: &BigInt, b: &BigInt) -> BigInt {
+    let q = a / b;
+    if (a % b).is_negative() {
+        if b.is_positive() {
+            q - 1
+        } else {
+            q + 1
+        }
+    } else {
+        q
+    }
+}
+
+pub fn abs(a: &Number) -> Number {
+    match a {
+        Number::Integer(i) => match i.checked_abs() {
+            Some(i) => Number::Integer(i),
+            None => Number::big(BigInt::from(*i).abs()),
+        },
+        Number::Big(i) => Number::big(i.abs()),
+        Number::Float(n) => Number::Float(n.abs()),
+    }
+}
+
+// applies a rounding function to floats, producing an integer when the result is finite
+pub fn round_with(a: &Number, f: fn(f64) -> f64) -> Number {
+    match a {
+        Number::Float(n) => {
+            let rounded = f(*n);
+            match BigInt::from_f64(rounded) {
+                Some(i) => Number::big(i),
+                None => Number::Float(rounded),
+            }
+        }
+        _ => a.clone(),
+    }
+}
+
+pub fn compare(a: &Number, b: &Number) -> Option<Ordering> {
+    match (a, b) {
+        (Number::Integer(x), Number::Integer(y)) => Some(x.cmp(y)),
+        (Number::Float(x), Number::Float(y)) => x.partial_cmp(y),
+        // integral floats are compared with integers exactly, so numbers that are equal
+        // also have the same hash
+        (Number::Float(_), _) | (_, Number::Float(_)) => match (a.as_integer(), b.as_integer()) {
+            (Some(x), Some(y)) => Some(x.cmp(&y)),
+            _ => a.as_f64().partial_cmp(&b.as_f64()),
+        },
+        _ => Some(a.as_big().unwrap().cmp(&b.as_big().unwrap())),
+    }
+}
+
+// compares numbers by value, regardless of their representation
+pub fn values_equal(a: &ir::Value, b: &ir::Value) -> bool {
+    match pair(a, b) {
+        Some((a, b)) => compare(&a, &b) == Some(Ordering::Equal),
+        None => a == b,
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    thread,
+    time::{Duration, Instant},
+};
+
+// files are closed when their handle is dropped, buffered writes are flushed at that point
+#[derive(Debug)]
+pub enum FileHandle {
+    Reader(BufReader<File>),
+    Writer(BufWriter<File>),
+    Closed,
+}
+
+fn error(message: &str) -> io::Error {
+    io::Error::other(message)
+}
+
+// returns `None` at the end of the input. the line ending is not included
+pub fn read_line(reader: &mut dyn BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+
+    Ok(Some(line))
+}
+
+impl FileHandle {
+    pub fn reader(file: File) -> Self {
+        FileHandle::Reader(BufReader::new(file))
+    }
+
+    pub fn writer(file: File) -> Self {
+        FileHandle::Writer(BufWriter::new(file))
+    }
+
+    fn as_reader(&mut self) -> io::Result<&mut BufReader<File>> {
+        match self {
+            FileHandle::Reader(reader) => Ok(reader),
+            FileHandle::Writer(_) => Err(error("file is not open for reading")),
+            FileHandle::Closed => Err(error("file is closed")),
+        }
+    }
+
+    fn as_writer(&mut self) -> io::Result<&mut BufWriter<File>> {
+        match self {
+            FileHandle::Writer(writer) => Ok(writer),
+            FileHandle::Reader(_) => Err(error("file is not open for writing")),
+            FileHandle::Closed => Err(error("file is closed")),
+        }
+    }
+
+    pub fn read_line(&mut self) -> io::Result<Option<String>> {
+        read_line(self.as_reader()?)
+    }
+
+    pub fn read(&mut self, count: usize) -> io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.as_reader()?
+            .take(count as u64)
+            .read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    pub fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.as_writer()?.write_all(data)
+    }
+
+    pub fn seek(&mut self, position: u64) -> io::Result<u64> {
+        match self {
+            FileHandle::Reader(reader) => reader.seek(SeekFrom::Start(position)),
+            FileHandle::Writer(writer) => writer.seek(SeekFrom::Start(position)),
+            FileHandle::Closed => Err(error("file is closed")),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        match self {
+            FileHandle::Writer(writer) => writer.flush(),
+            FileHandle::Reader(_) => Ok(()),
+            FileHandle::Closed => Err(error("file is closed")),
+        }
+    }
+
+    pub fn close(&mut self) -> io::Result<()> {
+        if let FileHandle::Closed = self {
+            return Ok(());
+        }
+
+        let result = self.flush();
+        *self = FileHandle::Closed;
+        result
+    }
+}
+
+#[derive(Debug)]
+pub enum Socket {
+    Open {
+        reader: BufReader<TcpStream>,
+        stream: TcpStream,
+    },
+    Closed,
+}
+
+impl Socket {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        Ok(Socket::Open {
+            reader: BufReader::new(stream.try_clone()?),
+            stream,
+        })
+    }
+
+    fn parts(&mut self) -> io::Result<(&mut BufReader<TcpStream>, &mut TcpStream)> {
+        match self {
+            Socket::Open { reader, stream } => Ok((reader, stream)),
+            Socket::Closed => Err(error("socket is closed")),
+        }
+    }
+
+    pub fn read_line(&mut self) -> io::Result<Option<String>> {
+        read_line(self.parts()?.0)
+    }
+
+    // reads up to and including the next newline, but no more than `limit` bytes.
+    // a result of `limit` bytes without a newline means the line is longer
+    pub fn read_line_at_most(&mut self, limit: usize) -> io::Result<Option<Vec<u8>>> {
+        let mut line = Vec::new();
+        if self
+            .parts()?
+            .0
+            .take(limit as u64)
+            .read_until(b'\n', &mut line)?
+            == 0
+        {
+            return Ok(None);
+        }
+        Ok(Some(line))
+    }
+
+    // returns the bytes that are available, up to `count`. an empty result means
+    // the other side has closed the connection
+    pub fn read(&mut self, count: usize) -> io::Result<Vec<u8>> {
+        let reader = self.parts()?.0;
+        let available = reader.fill_buf()?;
+        let data = available[..count.min(available.len())].to_vec();
+        reader.consume(data.len());
+        Ok(data)
+    }
+
+    // the buffer grows as data arrives, so a size announced by the other side
+    // doesn't allocate anything up front
+    pub fn read_exact(&mut self, count: usize) -> io::Result<Vec<u8>> {
+        let data = self.read_at_most(count)?;
+        if data.len() < count {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        Ok(data)
+    }
+
+    // reads until the connection is closed or `limit` bytes have arrived
+    pub fn read_at_most(&mut self, limit: usize) -> io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.parts()?.0.take(limit as u64).read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    pub fn read_all(&mut self) -> io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.parts()?.0.read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    pub fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        self.parts()?.1.write_all(data)
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        let stream = self.parts()?.1;
+        stream.set_read_timeout(timeout)?;
+        stream.set_write_timeout(timeout)
+    }
+
+    pub fn local_addr(&mut self) -> io::Result<SocketAddr> {
+        self.parts()?.1.local_addr()
+    }
+
+    pub fn peer_addr(&mut self) -> io::Result<SocketAddr> {
+        self.parts()?.1.peer_addr()
+    }
+
+    pub fn close(&mut self) {
+        if let Socket::Open { stream, .. } = self {
+            // the peer may already be gone, which is fine when closing
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+        *self = Socket::Closed;
+    }
+}
+
+#[derive(Debug)]
+pub struct Listener {
+    listener: Option<TcpListener>,
+    timeout: Option<Duration>,
+}
+
+impl Listener {
+    pub fn bind(address: &str) -> io::Result<Self> {
+        Ok(Self {
+            listener: Some(TcpListener::bind(address)?),
+            timeout: None,
+        })
+    }
+
+    fn listener(&self) -> io::Result<&TcpListener> {
+        self.listener
+            .as_ref()
+            .ok_or_else(|| error("listener is closed"))
+    }
+
+    // std has no accept timeout, so a listener with a timeout is polled
+    pub fn accept(&mut self) -> io::Result<Socket> {
+        let listener = self.listener()?;
+        // a deadline too far away to represent is the same as none
+        let deadline = self
+            .timeout
+            .and_then(|timeout| Instant::now().checked_add(timeout));
+        let stream = match deadline {
+            None => listener.accept()?.0,
+            Some(deadline) => {
+                listener.set_nonblocking(true)?;
+                let result = loop {
+                    match listener.accept() {
+                        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                            if Instant::now() >= deadline {
+                                break Err(io::Error::from(io::ErrorKind::TimedOut));
+                            }
+                            thread::sleep(Duration::from_millis(5));
+                        }
+                        result => break result,
+                    }
+                };
+                listener.set_nonblocking(false)?;
+                let stream = result?.0;
+                stream.set_nonblocking(false)?;
+                stream
+            }
+        };
+        Socket::new(stream)
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener()?.local_addr()
+    }
+
+    pub fn close(&mut self) {
+        self.listener = None;
+    }
+}
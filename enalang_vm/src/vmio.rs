@@ -0,0 +1,202 @@
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    terminal,
+};
+use std::{
+    cell::{RefCell, RefMut},
+    io::{self, BufRead, BufReader, Cursor, IsTerminal, Write},
+};
+
+fn no_terminal() -> io::Error {
+    io::Error::other("not a terminal")
+}
+
+// where the vm reads its input and writes its output. the streams are borrowed
+// for a single operation, so implementations can share them with the embedder
+pub trait VMIO {
+    fn stdout(&self) -> RefMut<'_, dyn Write>;
+    fn stderr(&self) -> RefMut<'_, dyn Write>;
+    fn stdin(&self) -> RefMut<'_, dyn BufRead>;
+    // debug output of the vm and the heap, like `CALL_DEBUG` and `GC_DEBUG`
+    fn diagnostics(&self) -> RefMut<'_, dyn Write>;
+
+    // the terminal behind the streams, used by the `term` natives. by default
+    // there is none
+    fn is_terminal(&self) -> bool {
+        false
+    }
+
+    // columns, then rows
+    fn terminal_size(&self) -> io::Result<(u16, u16)> {
+        Err(no_terminal())
+    }
+
+    fn set_raw_mode(&self, _enable: bool) -> io::Result<()> {
+        Err(no_terminal())
+    }
+
+    // waits for a key press and returns its name, like `ctrl+c`
+    fn read_key(&self) -> io::Result<String> {
+        Err(no_terminal())
+    }
+}
+
+// the streams of the current process. diagnostics go to stderr
+pub struct ProcessIO {
+    stdout: RefCell<io::Stdout>,
+    stderr: RefCell<io::Stderr>,
+    stdin: RefCell<BufReader<io::Stdin>>,
+}
+
+impl ProcessIO {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Default for ProcessIO {
+    fn default() -> Self {
+        Self {
+            stdout: RefCell::new(io::stdout()),
+            stderr: RefCell::new(io::stderr()),
+            stdin: RefCell::new(BufReader::new(io::stdin())),
+        }
+    }
+}
+
+// the name `term.read_key` pushes for a key, or `None` for keys it ignores
+fn key_name(key: KeyEvent) -> Option<String> {
+    let name = match key.code {
+        KeyCode::Char(' ') => String::from("space"),
+        KeyCode::Char(ch) => ch.to_string(),
+        KeyCode::Enter => String::from("enter"),
+        KeyCode::Esc => String::from("esc"),
+        KeyCode::Backspace => String::from("backspace"),
+        KeyCode::Tab => String::from("tab"),
+        KeyCode::BackTab => String::from("backtab"),
+        KeyCode::Delete => String::from("delete"),
+        KeyCode::Insert => String::from("insert"),
+        KeyCode::Up => String::from("up"),
+        KeyCode::Down => String::from("down"),
+        KeyCode::Left => String::from("left"),
+        KeyCode::Right => String::from("right"),
+        KeyCode::Home => String::from("home"),
+        KeyCode::End => String::from("end"),
+        KeyCode::PageUp => String::from("page_up"),
+        KeyCode::PageDown => String::from("page_down"),
+        KeyCode::F(n) => format!("f{n}"),
+        _ => return None,
+    };
+
+    let mut prefix = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        prefix.push_str("ctrl+");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str("alt+");
+    }
+    Some(prefix + &name)
+}
+
+impl VMIO for ProcessIO {
+    fn stdout(&self) -> RefMut<'_, dyn Write> {
+        self.stdout.borrow_mut()
+    }
+
+    fn stderr(&self) -> RefMut<'_, dyn Write> {
+        self.stderr.borrow_mut()
+    }
+
+    fn stdin(&self) -> RefMut<'_, dyn BufRead> {
+        self.stdin.borrow_mut()
+    }
+
+    fn diagnostics(&self) -> RefMut<'_, dyn Write> {
+        self.stderr.borrow_mut()
+    }
+
+    fn is_terminal(&self) -> bool {
+        io::stdout().is_terminal()
+    }
+
+    fn terminal_size(&self) -> io::Result<(u16, u16)> {
+        terminal::size()
+    }
+
+    fn set_raw_mode(&self, enable: bool) -> io::Result<()> {
+        if enable {
+            terminal::enable_raw_mode()
+        } else {
+            terminal::disable_raw_mode()
+        }
+    }
+
+    fn read_key(&self) -> io::Result<String> {
+        loop {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Release {
+                    continue;
+                }
+                if let Some(name) = key_name(key) {
+                    return Ok(name);
+                }
+            }
+        }
+    }
+}
+
+// reads from a fixed input and collects everything written, e.g. for tests.
+// it has no terminal
+#[derive(Default)]
+pub struct MemoryIO {
+    stdout: RefCell<Vec<u8>>,
+    stderr: RefCell<Vec<u8>>,
+    stdin: RefCell<Cursor<Vec<u8>>>,
+    diagnostics: RefCell<Vec<u8>>,
+}
+
+impl MemoryIO {
+    pub fn new(input: impl Into<Vec<u8>>) -> Self {
+        Self {
+            stdin: RefCell::new(Cursor::new(input.into())),
+            ..Default::default()
+        }
+    }
+
+    pub fn stdout_contents(&self) -> String {
+        String::from_utf8_lossy(&self.stdout.borrow()).into_owned()
+    }
+
+    pub fn stderr_contents(&self) -> String {
+        String::from_utf8_lossy(&self.stderr.borrow()).into_owned()
+    }
+
+    pub fn diagnostics_contents(&self) -> String {
+        String::from_utf8_lossy(&self.diagnostics.borrow()).into_owned()
+    }
+
+    // empties the output buffers, e.g. between two commands of the repl
+    pub fn clear(&self) {
+        self.stdout.borrow_mut().clear();
+        self.stderr.borrow_mut().clear();
+        self.diagnostics.borrow_mut().clear();
+    }
+}
+
+impl VMIO for MemoryIO {
+    fn stdout(&self) -> RefMut<'_, dyn Write> {
+        self.stdout.borrow_mut()
+    }
+
+    fn stderr(&self) -> RefMut<'_, dyn Write> {
+        self.stderr.borrow_mut()
+    }
+
+    fn stdin(&self) -> RefMut<'_, dyn BufRead> {
+        self.stdin.borrow_mut()
+    }
+
+    fn diagnostics(&self) -> RefMut<'_, dyn Write> {
+        self.diagnostics.borrow_mut()
+    }
+}
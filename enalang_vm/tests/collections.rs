@@ -0,0 +1,168 @@
+mod common;
+
+use common::{call, call_one, int, string};
+use enalang_ir as ir;
+use enalang_vm::machine::{VMError, VM};
+
+fn top(vm: &VM) -> ir::Value {
+    vm.stack.last().cloned().unwrap()
+}
+
+fn pointer(value: &ir::Value) -> usize {
+    value.as_heap_pointer().unwrap()
+}
+
+#[test]
+fn array_operations() {
+    let (mut vm, _) = common::vm();
+    call(&mut vm, "array.new", vec![]).unwrap();
+    let array = top(&vm);
+    for i in 1..=3 {
+        call(&mut vm, "array.push", vec![int(i), array.clone()]).unwrap();
+    }
+    assert_eq!(
+        call_one(&mut vm, "array.len", vec![array.clone()]),
+        Ok(int(3))
+    );
+    assert_eq!(
+        call_one(&mut vm, "array.get", vec![int(1), array.clone()]),
+        Ok(int(2))
+    );
+
+    call(
+        &mut vm,
+        "array.set",
+        vec![string("two"), int(1), array.clone()],
+    )
+    .unwrap();
+    assert_eq!(
+        call_one(&mut vm, "array.get", vec![int(1), array.clone()]),
+        Ok(string("two"))
+    );
+    call(&mut vm, "array.insert", vec![int(0), int(0), array.clone()]).unwrap();
+    assert_eq!(
+        call_one(&mut vm, "array.remove", vec![int(3), array.clone()]),
+        Ok(int(3))
+    );
+    assert_eq!(
+        call_one(&mut vm, "array.pop", vec![array.clone()]),
+        Ok(string("two"))
+    );
+    assert_eq!(
+        call_one(&mut vm, "array.len", vec![array.clone()]),
+        Ok(int(2))
+    );
+    assert_eq!(
+        call_one(&mut vm, "array.get", vec![int(0), array.clone()]),
+        Ok(int(0))
+    );
+}
+
+#[test]
+fn array_bounds() {
+    let (mut vm, _) = common::vm();
+    call(&mut vm, "array.new", vec![]).unwrap();
+    let array = top(&vm);
+    call(&mut vm, "array.push", vec![int(1), array.clone()]).unwrap();
+
+    // reading past the end gives null, writing past it is an error
+    assert_eq!(
+        call_one(&mut vm, "array.get", vec![int(5), array.clone()]),
+        Ok(ir::Value::Null)
+    );
+    assert_eq!(
+        call(&mut vm, "array.set", vec![int(2), int(1), array.clone()]),
+        Err(VMError::IndexOutOfBounds(1))
+    );
+    assert_eq!(
+        call(&mut vm, "array.insert", vec![int(2), int(2), array.clone()]),
+        Err(VMError::IndexOutOfBounds(2))
+    );
+    assert_eq!(
+        call(&mut vm, "array.remove", vec![int(1), array.clone()]),
+        Err(VMError::IndexOutOfBounds(1))
+    );
+    assert_eq!(
+        call(&mut vm, "array.len", vec![int(1)]),
+        Err(VMError::ExpectedArray)
+    );
+    assert_eq!(call_one(&mut vm, "array.len", vec![array]), Ok(int(1)));
+}
+
+#[test]
+fn map_operations() {
+    let (mut vm, _) = common::vm();
+    call(&mut vm, "map.new", vec![]).unwrap();
+    let map = top(&vm);
+    call(
+        &mut vm,
+        "map.insert",
+        vec![int(1), string("a"), map.clone()],
+    )
+    .unwrap();
+    call(
+        &mut vm,
+        "map.insert",
+        vec![int(2), string("b"), map.clone()],
+    )
+    .unwrap();
+    call(
+        &mut vm,
+        "map.insert",
+        vec![int(3), string("a"), map.clone()],
+    )
+    .unwrap();
+
+    assert_eq!(call_one(&mut vm, "map.len", vec![map.clone()]), Ok(int(2)));
+    assert_eq!(
+        call_one(&mut vm, "map.get", vec![string("a"), map.clone()]),
+        Ok(int(3))
+    );
+    assert_eq!(
+        call_one(&mut vm, "map.get", vec![string("c"), map.clone()]),
+        Ok(ir::Value::Null)
+    );
+    assert_eq!(
+        call_one(&mut vm, "map.has?", vec![string("b"), map.clone()]),
+        Ok(ir::Value::Boolean(true))
+    );
+    assert_eq!(
+        call_one(&mut vm, "map.remove", vec![string("b"), map.clone()]),
+        Ok(int(2))
+    );
+    assert_eq!(
+        call_one(&mut vm, "map.remove", vec![string("b"), map.clone()]),
+        Ok(ir::Value::Null)
+    );
+    assert_eq!(call_one(&mut vm, "map.len", vec![map]), Ok(int(1)));
+}
+
+#[test]
+fn nested_values_are_freed_with_their_parent() {
+    let (mut vm, _) = common::vm();
+    call(&mut vm, "map.new", vec![]).unwrap();
+    let map = top(&vm);
+    call(&mut vm, "array.new", vec![]).unwrap();
+    let array = vm.stack.pop().unwrap();
+    call(&mut vm, "array.new", vec![]).unwrap();
+    let inner = vm.stack.pop().unwrap();
+
+    // the map holds the array, which holds the inner array. the raw pops above
+    // gave their references to the containers
+    vm.stack.push(inner.clone());
+    call(&mut vm, "array.push", vec![array.clone()]).unwrap();
+    vm.stack.push(array.clone());
+    call(&mut vm, "map.insert", vec![string("list"), map.clone()]).unwrap();
+    assert_eq!(
+        call_one(&mut vm, "map.get", vec![string("list"), map.clone()]),
+        Ok(array.clone())
+    );
+
+    for value in [&map, &array, &inner] {
+        assert!(vm.heap.get_object(pointer(value)).is_some());
+    }
+    vm.pop().unwrap();
+    for value in [&map, &array, &inner] {
+        assert!(vm.heap.get_object(pointer(value)).is_none());
+    }
+}
@@ -0,0 +1,68 @@
+#![allow(dead_code)]
+
+use enalang_ir as ir;
+use enalang_vm::{
+    blocks,
+    machine::{VMError, VMOptions, VM},
+    native,
+    vmio::MemoryIO,
+};
+use flexstr::ToLocalStr;
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+// a vm with all natives loaded and its streams kept in memory
+pub fn vm() -> (VM, Rc<MemoryIO>) {
+    vm_with(native::NativeGroup::new(""))
+}
+
+// the same, with some more natives
+pub fn vm_with(extra: native::NativeGroup) -> (VM, Rc<MemoryIO>) {
+    vm_with_options(extra, VMOptions::new())
+}
+
+pub fn vm_with_options(extra: native::NativeGroup, options: VMOptions) -> (VM, Rc<MemoryIO>) {
+    let mut natives = native::group();
+    natives.add_child(&extra).unwrap();
+    let io = Rc::new(MemoryIO::new(""));
+    let mut vm = VM::new(options);
+    vm.set_io(io.clone());
+    let blocks = blocks::Blocks::new(natives, ir::IR::default()).unwrap();
+    vm.run(&"nop".to_local_str(), blocks).unwrap();
+    (vm, io)
+}
+
+// pushes the arguments in order, so the last one ends up on top, and calls a block
+pub fn call(vm: &mut VM, name: &str, args: Vec<ir::Value>) -> Result<(), VMError> {
+    for arg in args {
+        vm.push(arg)?;
+    }
+    vm.run_block(&name.to_local_str()).map(|_| ())
+}
+
+pub fn call_one(vm: &mut VM, name: &str, args: Vec<ir::Value>) -> Result<ir::Value, VMError> {
+    call(vm, name, args)?;
+    vm.pop()
+}
+
+pub fn string(st: &str) -> ir::Value {
+    ir::Value::String(st.to_local_str())
+}
+
+pub fn int(i: i64) -> ir::Value {
+    ir::Value::Integer(i)
+}
+
+// a fresh directory under the system temp dir, unique to this process and test
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ena-test-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+pub fn path(path: &Path) -> ir::Value {
+    string(path.to_str().unwrap())
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::{call, call_one, int, string};
+use enalang_ir as ir;
+use enalang_vm::{
+    heap::HeapObject,
+    machine::{VMError, VM},
+    native::{ena_native, NativeGroup},
+};
+use flexstr::LocalStr;
+
+#[ena_native(name = "len")]
+fn array_len(vm: &mut VM, array: ir::Value) -> Result<usize, VMError> {
+    let pointer = match array {
+        ir::Value::Array(pointer) => pointer,
+        _ => return Err(VMError::ExpectedArray),
+    };
+    match vm.heap.get_object(pointer) {
+        Some(HeapObject::Array(items)) => Ok(items.len()),
+        _ => Err(VMError::BadPointer(pointer)),
+    }
+}
+
+#[ena_native]
+fn greet(name: LocalStr, excited: bool) -> Option<String> {
+    if name.is_empty() {
+        return None;
+    }
+    Some(format!("Hello, {name}{}", if excited { "!" } else { "." }))
+}
+
+// the vm can be taken anywhere in the arguments, under any path
+#[ena_native]
+fn depth(below: i64, vm: &mut enalang_vm::machine::VM) -> i64 {
+    below + vm.stack.len() as i64
+}
+
+fn natives() -> NativeGroup {
+    let mut group = NativeGroup::new("test");
+    register_array_len(&mut group).unwrap();
+    register_greet(&mut group).unwrap();
+    register_depth(&mut group).unwrap();
+    group
+}
+
+#[test]
+fn arguments_are_popped_from_the_top() {
+    let (mut vm, _) = common::vm_with(natives());
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "test.greet",
+            vec![ir::Value::Boolean(true), string("ena")]
+        ),
+        Ok(string("Hello, ena!"))
+    );
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "test.greet",
+            vec![ir::Value::Boolean(false), string("")]
+        ),
+        Ok(ir::Value::Null)
+    );
+    assert_eq!(
+        call(&mut vm, "test.greet", vec![int(1), string("ena")]),
+        Err(VMError::ExpectedBoolean)
+    );
+}
+
+#[test]
+fn value_arguments_stay_alive_during_the_call() {
+    let (mut vm, _) = common::vm_with(natives());
+    call(&mut vm, "array.new", vec![]).unwrap();
+    let pointer = match vm.stack.last() {
+        Some(ir::Value::Array(pointer)) => *pointer,
+        other => panic!("expected an array, got {other:?}"),
+    };
+
+    // the stack held the only reference, so the array is freed after the call
+    assert_eq!(call_one(&mut vm, "test.len", vec![]), Ok(int(0)));
+    assert!(vm.heap.get_object(pointer).is_none());
+
+    assert_eq!(
+        call(&mut vm, "test.len", vec![int(1)]),
+        Err(VMError::ExpectedArray)
+    );
+}
+
+#[test]
+fn the_vm_is_passed_by_reference() {
+    let (mut vm, _) = common::vm_with(natives());
+    assert_eq!(
+        call_one(&mut vm, "test.depth", vec![string("x"), int(10)]),
+        Ok(int(11))
+    );
+}
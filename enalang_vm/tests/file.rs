@@ -0,0 +1,144 @@
+mod common;
+
+use common::{call, call_one, int, path, string};
+use enalang_ir as ir;
+use enalang_vm::machine::{VMError, VM};
+use std::{fs, path::Path};
+
+fn open(vm: &mut VM, mode: &str, file: &Path) -> ir::Value {
+    call(vm, &format!("file.open_{mode}"), vec![path(file)]).unwrap();
+    vm.stack.last().cloned().unwrap()
+}
+
+fn exception(result: Result<(), VMError>) -> String {
+    match result {
+        Err(VMError::RuntimeException(ir::Value::String(st))) => st.to_string(),
+        other => panic!("expected an exception, got {other:?}"),
+    }
+}
+
+#[test]
+fn writing_and_reading_handles() {
+    let dir = common::temp_dir("handles");
+    let file = dir.join("log.txt");
+    let (mut vm, _) = common::vm();
+
+    let handle = open(&mut vm, "write", &file);
+    call(
+        &mut vm,
+        "file.write",
+        vec![string("one\r\n"), handle.clone()],
+    )
+    .unwrap();
+    call(
+        &mut vm,
+        "file.write",
+        vec![ir::Value::Bytes(b"two\nthree".to_vec()), handle.clone()],
+    )
+    .unwrap();
+    call(&mut vm, "file.close", vec![handle]).unwrap();
+    vm.pop().unwrap();
+
+    let handle = open(&mut vm, "append", &file);
+    call(&mut vm, "file.write", vec![string("!"), handle.clone()]).unwrap();
+    call(&mut vm, "file.flush", vec![handle]).unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "one\r\ntwo\nthree!");
+    vm.pop().unwrap();
+
+    let handle = open(&mut vm, "read", &file);
+    for line in ["one", "two", "three!"] {
+        assert_eq!(
+            call_one(&mut vm, "file.read_line", vec![handle.clone()]),
+            Ok(string(line))
+        );
+    }
+    assert_eq!(
+        call_one(&mut vm, "file.read_line", vec![handle.clone()]),
+        Ok(ir::Value::Null)
+    );
+
+    call(&mut vm, "file.seek", vec![int(5), handle.clone()]).unwrap();
+    assert_eq!(
+        call_one(&mut vm, "file.read", vec![int(3), handle.clone()]),
+        Ok(ir::Value::Bytes(b"two".to_vec()))
+    );
+    call(&mut vm, "file.seek", vec![int(12), handle.clone()]).unwrap();
+    assert_eq!(
+        call_one(&mut vm, "file.read", vec![int(100), handle.clone()]),
+        Ok(ir::Value::Bytes(b"ee!".to_vec()))
+    );
+    assert_eq!(
+        call_one(&mut vm, "file.read", vec![int(1), handle]),
+        Ok(ir::Value::Bytes(vec![]))
+    );
+    vm.pop().unwrap();
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn handles_are_closed_when_dropped() {
+    let dir = common::temp_dir("dropped");
+    let file = dir.join("out.txt");
+    let (mut vm, _) = common::vm();
+
+    let handle = open(&mut vm, "write", &file);
+    call(&mut vm, "file.write", vec![string("buffered"), handle]).unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "");
+
+    // the last reference is gone, so the buffered write is flushed
+    vm.pop().unwrap();
+    assert!(vm.heap.get_object(0).is_none());
+    assert_eq!(fs::read_to_string(&file).unwrap(), "buffered");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn handle_failures_are_exceptions() {
+    let dir = common::temp_dir("handle-failures");
+    let file = dir.join("file.txt");
+    fs::write(&file, "text").unwrap();
+    let (mut vm, _) = common::vm();
+
+    let missing = dir.join("missing");
+    assert!(
+        exception(call(&mut vm, "file.open_read", vec![path(&missing)]))
+            .starts_with(&format!("{}: ", missing.display()))
+    );
+
+    let handle = open(&mut vm, "read", &file);
+    assert_eq!(
+        exception(call(
+            &mut vm,
+            "file.write",
+            vec![string("x"), handle.clone()]
+        )),
+        "file: file is not open for writing"
+    );
+    call(&mut vm, "file.close", vec![handle.clone()]).unwrap();
+    // closing twice is fine, everything else fails
+    call(&mut vm, "file.close", vec![handle.clone()]).unwrap();
+    assert_eq!(
+        exception(call(&mut vm, "file.read_line", vec![handle.clone()])),
+        "file: file is closed"
+    );
+    assert_eq!(
+        exception(call(&mut vm, "file.seek", vec![int(0), handle.clone()])),
+        "file: file is closed"
+    );
+    vm.pop().unwrap();
+
+    let handle = open(&mut vm, "write", &file);
+    assert_eq!(
+        exception(call(&mut vm, "file.read", vec![int(1), handle])),
+        "file: file is not open for reading"
+    );
+    assert_eq!(
+        call(
+            &mut vm,
+            "file.write",
+            vec![string("x"), string("not a handle")]
+        ),
+        Err(VMError::ExpectedFile)
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::{call_one, int, string};
+use enalang_ir as ir;
+use enalang_vm::machine::VMError;
+
+fn hash(name: &str, data: ir::Value) -> Result<ir::Value, VMError> {
+    let (mut vm, _) = common::vm();
+    call_one(&mut vm, &format!("hash.{name}"), vec![data])
+}
+
+#[test]
+fn known_digests() {
+    let vectors = [
+        ("md5", "", "d41d8cd98f00b204e9800998ecf8427e"),
+        ("md5", "abc", "900150983cd24fb0d6963f7d28e17f72"),
+        ("sha1", "", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+        ("sha1", "abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+        (
+            "sha256",
+            "",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ),
+        (
+            "sha256",
+            "abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+        (
+            "sha256",
+            "The quick brown fox jumps over the lazy dog",
+            "d7a8fbb307d7809469ca9abcb0082e4f8d5651e46d3cdb762d02d0bf37c9e592",
+        ),
+    ];
+
+    for (name, input, expected) in vectors {
+        assert_eq!(hash(name, string(input)), Ok(string(expected)), "{name}");
+        assert_eq!(
+            hash(name, ir::Value::Bytes(input.as_bytes().to_vec())),
+            Ok(string(expected)),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn digests_need_contents() {
+    for name in ["md5", "sha1", "sha256"] {
+        assert_eq!(hash(name, int(1)), Err(VMError::ExpectedString), "{name}");
+    }
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::{call, call_one};
+use enalang_ir as ir;
+use enalang_vm::{heap::Heap, vmio::MemoryIO};
+use std::rc::Rc;
+
+fn heap() -> Heap {
+    Heap::new(true, false, Rc::new(MemoryIO::new("")))
+}
+
+#[test]
+fn weak_pointers_die_with_their_block() {
+    let mut heap = heap();
+    let pointer = heap.alloc(1).unwrap().pointer;
+    let weak = heap.downgrade(pointer).unwrap();
+    assert_eq!(heap.downgrade(pointer).unwrap(), weak);
+    assert_eq!(heap.upgrade(weak), Some(pointer));
+
+    heap.rc_minus(pointer).unwrap();
+    assert_eq!(heap.upgrade(weak), None);
+    // the pointer may be reused, the old weak pointer stays dead
+    let reused = heap.alloc(1).unwrap().pointer;
+    assert_eq!(reused, pointer);
+    assert_eq!(heap.upgrade(weak), None);
+}
+
+#[test]
+fn weak_pointers_follow_realloc() {
+    let mut heap = heap();
+    let pointer = heap.alloc(1).unwrap().pointer;
+    heap.set(pointer, ir::Value::Integer(7)).unwrap();
+    // the block right after is taken, so growing has to move
+    heap.alloc(1).unwrap();
+    let weak = heap.downgrade(pointer).unwrap();
+
+    let moved = heap.realloc(pointer, 4).unwrap();
+    assert_ne!(moved, pointer);
+    assert_eq!(heap.upgrade(weak), Some(moved));
+    assert_eq!(heap.get(moved), Some(ir::Value::Integer(7)));
+}
+
+#[test]
+fn upgrade_pushes_null_after_free() {
+    let (mut vm, _) = common::vm();
+    call(&mut vm, "array.new", vec![]).unwrap();
+    call(&mut vm, "dup", vec![]).unwrap();
+    call(&mut vm, "downgrade", vec![]).unwrap();
+    let weak = vm.stack.pop().unwrap();
+    assert!(matches!(weak, ir::Value::WeakPointer(_)));
+
+    assert_eq!(
+        call_one(&mut vm, "weak_alive?", vec![weak.clone()]),
+        Ok(ir::Value::Boolean(true))
+    );
+    assert!(matches!(
+        call_one(&mut vm, "upgrade", vec![weak.clone()]),
+        Ok(ir::Value::Array(_))
+    ));
+
+    // the stack held the only strong reference
+    vm.pop().unwrap();
+    assert_eq!(
+        call_one(&mut vm, "weak_alive?", vec![weak.clone()]),
+        Ok(ir::Value::Boolean(false))
+    );
+    assert_eq!(
+        call_one(&mut vm, "upgrade", vec![weak]),
+        Ok(ir::Value::Null)
+    );
+    assert_eq!(vm.stack.len(), 0);
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{call, call_one, int};
+use enalang_ir as ir;
+use enalang_vm::{
+    machine::{VMError, VM},
+    native::NativeGroup,
+};
+use std::{cell::Cell, rc::Rc};
+
+struct Counter {
+    count: i64,
+    drops: Rc<Cell<usize>>,
+}
+
+impl Drop for Counter {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+fn natives(drops: Rc<Cell<usize>>) -> NativeGroup {
+    let mut group = NativeGroup::new("counter");
+    group
+        .add_closure("new", move |mut ctx| {
+            let counter = Counter {
+                count: 0,
+                drops: drops.clone(),
+            };
+            ctx.push_host("counter", counter)
+        })
+        .unwrap();
+    group
+        .add_native("incr", |mut ctx| {
+            let count = ctx.with_host(|counter: &mut Counter| {
+                counter.count += 1;
+                counter.count
+            })?;
+            ctx.vm.push(int(count))
+        })
+        .unwrap();
+    group
+        .add_native("as_string", |mut ctx| {
+            ctx.with_host(|_: &mut String| ())?;
+            Ok(())
+        })
+        .unwrap();
+    group
+}
+
+fn top(vm: &VM) -> ir::Value {
+    vm.stack.last().cloned().unwrap()
+}
+
+#[test]
+fn host_objects_pass_through_ena() {
+    let drops = Rc::new(Cell::new(0));
+    let (mut vm, _) = common::vm_with(natives(drops.clone()));
+
+    // keep the counter only in an array
+    call(&mut vm, "array.new", vec![]).unwrap();
+    call(&mut vm, "dup", vec![]).unwrap();
+    call(&mut vm, "counter.new", vec![]).unwrap();
+    let counter = top(&vm);
+    assert_eq!(
+        call_one(&mut vm, "is_host", vec![counter]),
+        Ok(ir::Value::Boolean(true))
+    );
+    call(&mut vm, "swap", vec![]).unwrap();
+    call(&mut vm, "array.push", vec![]).unwrap();
+    let array = top(&vm);
+
+    for expected in 1..=2 {
+        call(&mut vm, "array.get", vec![int(0), array.clone()]).unwrap();
+        assert_eq!(call_one(&mut vm, "counter.incr", vec![]), Ok(int(expected)));
+    }
+
+    call(&mut vm, "array.get", vec![int(0), array.clone()]).unwrap();
+    match call_one(&mut vm, "into_string", vec![]) {
+        Ok(ir::Value::String(st)) => assert!(st.starts_with("<counter "), "{st}"),
+        other => panic!("expected a string, got {other:?}"),
+    }
+
+    call(&mut vm, "array.get", vec![int(0), array.clone()]).unwrap();
+    assert!(matches!(
+        call(&mut vm, "counter.as_string", vec![]),
+        Err(VMError::ExpectedHost(_))
+    ));
+    assert!(matches!(
+        call(&mut vm, "counter.incr", vec![int(1)]),
+        Err(VMError::ExpectedHost(_))
+    ));
+
+    // dropping the array releases the last reference
+    assert_eq!(drops.get(), 0);
+    vm.pop().unwrap();
+    assert_eq!(drops.get(), 1);
+}
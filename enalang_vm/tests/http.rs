@@ -0,0 +1,78 @@
+mod common;
+
+use common::{call, string};
+use enalang_ir as ir;
+use enalang_vm::machine::{VMError, VM};
+
+fn top(vm: &VM) -> ir::Value {
+    vm.stack.last().cloned().unwrap()
+}
+
+// sends a request with one field set, to a port nothing listens on. the message
+// is built before connecting, so an invalid field fails first
+fn fetch_with(field: &str, value: ir::Value) -> Result<(), VMError> {
+    let (mut vm, _) = common::vm();
+    call(&mut vm, "map.new", vec![]).unwrap();
+    let request = top(&vm);
+    call(&mut vm, "map.insert", vec![value, string(field), request]).unwrap();
+    call(&mut vm, "http.fetch", vec![string("http://127.0.0.1:1/")])
+}
+
+fn fetch_with_header(name: &str, value: &str) -> Result<(), VMError> {
+    let (mut vm, _) = common::vm();
+    call(&mut vm, "map.new", vec![]).unwrap();
+    let headers = top(&vm);
+    call(
+        &mut vm,
+        "map.insert",
+        vec![string(value), string(name), headers.clone()],
+    )
+    .unwrap();
+    call(&mut vm, "map.new", vec![]).unwrap();
+    let request = vm.stack.pop().unwrap();
+    call(
+        &mut vm,
+        "map.insert",
+        vec![headers, string("headers"), request.clone()],
+    )
+    .unwrap();
+    // the request now holds the only reference to the headers
+    vm.pop().unwrap();
+    vm.stack.push(request);
+    call(&mut vm, "http.fetch", vec![string("http://127.0.0.1:1/")])
+}
+
+fn assert_invalid(result: Result<(), VMError>, what: &str) {
+    match result {
+        Err(VMError::RuntimeException(ir::Value::String(message))) => {
+            assert!(message.starts_with(&format!("invalid {what}")), "{message}")
+        }
+        other => panic!("expected an exception, got {other:?}"),
+    }
+}
+
+#[test]
+fn line_breaks_are_rejected() {
+    assert_invalid(
+        fetch_with("method", string("GET / HTTP/1.1\r\nX")),
+        "method",
+    );
+    assert_invalid(fetch_with("path", string("/\nX-Injected: 1")), "path");
+    assert_invalid(
+        fetch_with_header("X-Test", "a\r\nSet-Cookie: b"),
+        "header value",
+    );
+    assert_invalid(fetch_with_header("X-Test\r\nA", "b"), "header name");
+    assert_invalid(fetch_with_header("X-Test: a", "b"), "header name");
+}
+
+#[test]
+fn valid_requests_reach_the_network() {
+    // nothing listens on port 1, so the request fails only when connecting
+    match fetch_with_header("X-Test", "a: b") {
+        Err(VMError::RuntimeException(ir::Value::String(message))) => {
+            assert!(message.starts_with("127.0.0.1:1"), "{message}")
+        }
+        other => panic!("expected an exception, got {other:?}"),
+    }
+}
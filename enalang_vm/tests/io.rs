@@ -0,0 +1,167 @@
+mod common;
+
+use common::{call, call_one, int, path, string};
+use enalang_ir as ir;
+use enalang_vm::machine::{VMError, VM};
+use std::fs;
+
+fn io(vm: &mut VM, name: &str, args: Vec<ir::Value>) -> Result<(), VMError> {
+    call(vm, &format!("ena.vm.io.{name}"), args)
+}
+
+fn io_one(vm: &mut VM, name: &str, args: Vec<ir::Value>) -> Result<ir::Value, VMError> {
+    call_one(vm, &format!("ena.vm.io.{name}"), args)
+}
+
+fn is_exception(result: Result<(), VMError>, prefix: &str) -> bool {
+    matches!(result, Err(VMError::RuntimeException(ir::Value::String(st))) if st.starts_with(prefix))
+}
+
+#[test]
+fn reading_and_writing_files() {
+    let dir = common::temp_dir("files");
+    let file = dir.join("a.txt");
+    let (mut vm, _) = common::vm();
+
+    io(&mut vm, "write_file", vec![string("one\n"), path(&file)]).unwrap();
+    io(&mut vm, "append_file", vec![string("two\n"), path(&file)]).unwrap();
+    assert_eq!(
+        io_one(&mut vm, "read_file", vec![path(&file)]),
+        Ok(string("one\ntwo\n"))
+    );
+    io(
+        &mut vm,
+        "write_file_bytes",
+        vec![ir::Value::Bytes(vec![0, 255]), path(&file)],
+    )
+    .unwrap();
+    assert_eq!(
+        io_one(&mut vm, "read_file_bytes", vec![path(&file)]),
+        Ok(ir::Value::Bytes(vec![0, 255]))
+    );
+    assert_eq!(io_one(&mut vm, "file_size", vec![path(&file)]), Ok(int(2)));
+    assert!(matches!(
+        io_one(&mut vm, "file_modified", vec![path(&file)]),
+        Ok(ir::Value::Integer(seconds)) if seconds > 0
+    ));
+
+    let copy = dir.join("b.txt");
+    io(&mut vm, "copy_file", vec![path(&copy), path(&file)]).unwrap();
+    assert_eq!(fs::read(&copy).unwrap(), vec![0, 255]);
+    let renamed = dir.join("c.txt");
+    io(&mut vm, "rename", vec![path(&renamed), path(&copy)]).unwrap();
+    assert_eq!(
+        io_one(&mut vm, "file_exists?", vec![path(&copy)]),
+        Ok(ir::Value::Boolean(false))
+    );
+    assert_eq!(
+        io_one(&mut vm, "file_exists?", vec![path(&renamed)]),
+        Ok(ir::Value::Boolean(true))
+    );
+
+    assert_eq!(
+        io_one(&mut vm, "list_files_in_dir", vec![path(&dir)]),
+        Ok(int(2))
+    );
+    let mut names = vm.stack.split_off(vm.stack.len() - 2);
+    names.sort_by_key(|x| format!("{x:?}"));
+    assert_eq!(names, vec![string("a.txt"), string("c.txt")]);
+
+    io(&mut vm, "delete_file", vec![path(&renamed)]).unwrap();
+    assert!(!renamed.exists());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn directories() {
+    let dir = common::temp_dir("dirs");
+    let nested = dir.join("a").join("b");
+    let (mut vm, _) = common::vm();
+
+    io(&mut vm, "create_dir", vec![path(&nested)]).unwrap();
+    assert_eq!(
+        io_one(&mut vm, "dir_exists?", vec![path(&nested)]),
+        Ok(ir::Value::Boolean(true))
+    );
+    assert_eq!(
+        io_one(&mut vm, "file_exists?", vec![path(&nested)]),
+        Ok(ir::Value::Boolean(false))
+    );
+
+    io(&mut vm, "remove_dir", vec![path(&nested)]).unwrap();
+    assert!(!nested.exists());
+    fs::write(dir.join("a").join("file"), "").unwrap();
+    assert!(is_exception(
+        io(&mut vm, "remove_dir", vec![path(&dir.join("a"))]),
+        dir.join("a").to_str().unwrap()
+    ));
+    io(&mut vm, "remove_dir_all", vec![path(&dir)]).unwrap();
+    assert!(!dir.exists());
+}
+
+#[test]
+fn failures_are_exceptions() {
+    let dir = common::temp_dir("failures");
+    let missing = dir.join("missing");
+    let (mut vm, _) = common::vm();
+    let prefix = format!("{}: ", missing.display());
+
+    for name in ["read_file", "read_file_bytes", "delete_file", "file_size"] {
+        assert!(
+            is_exception(io(&mut vm, name, vec![path(&missing)]), &prefix),
+            "{name}"
+        );
+    }
+    assert!(is_exception(
+        io(
+            &mut vm,
+            "copy_file",
+            vec![path(&dir.join("to")), path(&missing)]
+        ),
+        &prefix
+    ));
+    assert!(is_exception(
+        io(
+            &mut vm,
+            "write_file",
+            vec![string(""), path(&missing.join("x"))]
+        ),
+        missing.to_str().unwrap()
+    ));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn paths() {
+    let (mut vm, _) = common::vm();
+    assert_eq!(
+        io_one(&mut vm, "path_join", vec![string("b.txt"), string("a")]),
+        Ok(string(
+            std::path::Path::new("a").join("b.txt").to_str().unwrap()
+        ))
+    );
+    assert_eq!(
+        io_one(&mut vm, "path_dirname", vec![string("a/b.txt")]),
+        Ok(string("a"))
+    );
+    assert_eq!(
+        io_one(&mut vm, "path_basename", vec![string("a/b.txt")]),
+        Ok(string("b.txt"))
+    );
+    assert_eq!(
+        io_one(&mut vm, "path_extension", vec![string("a/b.txt")]),
+        Ok(string("txt"))
+    );
+    assert_eq!(
+        io_one(&mut vm, "path_extension", vec![string("a/b")]),
+        Ok(ir::Value::Null)
+    );
+    assert_eq!(
+        io_one(&mut vm, "path_dirname", vec![string("/")]),
+        Ok(ir::Value::Null)
+    );
+    assert_eq!(
+        io_one(&mut vm, "current_dir", vec![]),
+        Ok(path(&std::env::current_dir().unwrap()))
+    );
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use common::{call, call_one, int, string};
+use enalang_ir as ir;
+use enalang_vm::machine::VMError;
+
+fn is_exception(result: Result<ir::Value, VMError>, prefix: &str) -> bool {
+    matches!(result, Err(VMError::RuntimeException(ir::Value::String(message))) if message.starts_with(prefix))
+}
+
+#[test]
+fn parse_then_stringify_round_trips() {
+    let (mut vm, _) = common::vm();
+    for document in [
+        r#"{"b":[1,2.5,"x\n✓",null,true],"a":{"n":-3,"empty":[]}}"#,
+        r#"[{},[[]],"",false]"#,
+        "18446744073709551615",
+        "-0.5",
+    ] {
+        call(&mut vm, "json.parse", vec![string(document)]).unwrap();
+        assert_eq!(
+            call_one(&mut vm, "json.stringify", vec![]),
+            Ok(string(document))
+        );
+    }
+    assert_eq!(vm.stack.len(), 0);
+}
+
+#[test]
+fn integers_and_floats_stay_apart() {
+    let (mut vm, _) = common::vm();
+    assert_eq!(
+        call_one(&mut vm, "json.parse", vec![string("1")]),
+        Ok(int(1))
+    );
+    assert_eq!(
+        call_one(&mut vm, "json.parse", vec![string("1.0")]),
+        Ok(ir::Value::Number(1.0))
+    );
+    assert_eq!(
+        call_one(&mut vm, "json.parse", vec![string("18446744073709551615")]),
+        Ok(ir::Value::integer("18446744073709551615".parse().unwrap()))
+    );
+
+    assert_eq!(
+        call_one(&mut vm, "json.stringify", vec![int(1)]),
+        Ok(string("1"))
+    );
+    assert_eq!(
+        call_one(&mut vm, "json.stringify", vec![ir::Value::Number(1.0)]),
+        Ok(string("1.0"))
+    );
+    assert!(is_exception(
+        call_one(&mut vm, "json.stringify", vec![ir::Value::Number(f64::NAN)]),
+        "cannot convert"
+    ));
+}
+
+#[test]
+fn malformed_input_is_an_exception() {
+    let (mut vm, _) = common::vm();
+    for document in ["{", "[1,]", "nul", "{\"a\" 1}", "", "1 2"] {
+        assert!(
+            is_exception(
+                call_one(&mut vm, "json.parse", vec![string(document)]),
+                "invalid json"
+            ),
+            "{document}"
+        );
+    }
+    assert_eq!(vm.stack.len(), 0);
+}
+
+#[test]
+fn pretty_output_is_indented() {
+    let (mut vm, _) = common::vm();
+    call(&mut vm, "json.parse", vec![string(r#"{"a":[1,2],"b":{}}"#)]).unwrap();
+    assert_eq!(
+        call_one(&mut vm, "json.stringify_pretty", vec![]),
+        Ok(string("{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": {}\n}"))
+    );
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::{call, call_one, int};
+use enalang_ir as ir;
+use enalang_vm::{
+    heap::HeapObject,
+    machine::{VMError, VMOptions, VM},
+    native::{NativeGroup, NativeHandlerCtx},
+};
+use std::{cell::Cell, rc::Rc};
+
+fn answer(ctx: NativeHandlerCtx) -> Result<(), VMError> {
+    ctx.vm.push(int(42))
+}
+
+#[test]
+fn functions_and_closures_are_natives() {
+    let mut group = NativeGroup::new("app");
+    group.add_native("answer", answer).unwrap();
+    group.add_native("zero", |ctx| ctx.vm.push(int(0))).unwrap();
+
+    let calls = Rc::new(Cell::new(0));
+    let seen = calls.clone();
+    group
+        .add_closure("calls", move |ctx| {
+            seen.set(seen.get() + 1);
+            ctx.vm.push(int(seen.get()))
+        })
+        .unwrap();
+
+    let mut count = 0;
+    group
+        .add_closure_mut("next_id", move |ctx| {
+            count += 1;
+            ctx.vm.push(int(count))
+        })
+        .unwrap();
+
+    assert!(group.add_native("answer", answer).is_err());
+
+    let (mut vm, _) = common::vm_with(group);
+    assert_eq!(call_one(&mut vm, "app.answer", vec![]), Ok(int(42)));
+    assert_eq!(call_one(&mut vm, "app.zero", vec![]), Ok(int(0)));
+    assert_eq!(call_one(&mut vm, "app.calls", vec![]), Ok(int(1)));
+    assert_eq!(call_one(&mut vm, "app.calls", vec![]), Ok(int(2)));
+    assert_eq!(calls.get(), 2);
+    assert_eq!(call_one(&mut vm, "app.next_id", vec![]), Ok(int(1)));
+    assert_eq!(call_one(&mut vm, "app.next_id", vec![]), Ok(int(2)));
+}
+
+#[test]
+fn mutable_closures_cannot_reenter() {
+    let mut group = NativeGroup::new("app");
+    group
+        .add_closure_mut("again", |ctx| {
+            ctx.vm.run_block(&"app.again".into()).map(|_| ())
+        })
+        .unwrap();
+
+    let (mut vm, _) = common::vm_with(group);
+    assert_eq!(
+        call(&mut vm, "app.again", vec![]),
+        Err(VMError::NativeAlreadyRunning)
+    );
+}
+
+// ten random integers, then 0 to 9 shuffled
+fn random_sequence(vm: &mut VM, seed: i64) -> (Vec<ir::Value>, Vec<ir::Value>) {
+    call(vm, "ena.vm.seed_random", vec![int(seed)]).unwrap();
+    let numbers = (0..10)
+        .map(|_| call_one(vm, "ena.vm.random_int", vec![int(1000), int(1)]).unwrap())
+        .collect();
+
+    let mut values: Vec<ir::Value> = (0..10).map(int).collect();
+    values.push(int(10));
+    call(vm, "array.from_stack", values).unwrap();
+    let array = vm.stack.pop().unwrap();
+    call(vm, "ena.vm.shuffle", vec![array.clone()]).unwrap();
+    let shuffled = match array {
+        ir::Value::Array(pointer) => match vm.heap.get_object(pointer) {
+            Some(HeapObject::Array(values)) => values.clone(),
+            other => panic!("expected an array, got {other:?}"),
+        },
+        other => panic!("expected an array, got {other:?}"),
+    };
+    vm.handle_minus(array).unwrap();
+    (numbers, shuffled)
+}
+
+#[test]
+fn seeded_random_is_reproducible() {
+    let (mut vm, _) = common::vm();
+    let first = random_sequence(&mut vm, 42);
+    assert!(first.0.iter().all(|x| match x {
+        ir::Value::Integer(i) => (1..=1000).contains(i),
+        _ => false,
+    }));
+    assert_eq!(random_sequence(&mut vm, 42), first);
+
+    // a fresh vm gives the same sequence for the same seed
+    let (mut other, _) = common::vm();
+    assert_eq!(random_sequence(&mut other, 42), first);
+    assert_ne!(random_sequence(&mut other, 43), first);
+}
+
+#[test]
+fn seed_option_is_reproducible() {
+    let draw = |seed| {
+        let options = VMOptions {
+            seed: Some(seed),
+            ..VMOptions::new()
+        };
+        let (mut vm, _) = common::vm_with_options(NativeGroup::new(""), options);
+        (0..10)
+            .map(|_| call_one(&mut vm, "ena.vm.random", vec![]).unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(draw(7), draw(7));
+    assert_ne!(draw(7), draw(8));
+}
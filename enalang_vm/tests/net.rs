@@ -0,0 +1,72 @@
+mod common;
+
+use common::{call, call_one, string};
+use enalang_ir as ir;
+use enalang_vm::machine::{VMError, VM};
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    thread,
+};
+
+// the value on top of the stack, which stays there
+fn top(vm: &VM) -> ir::Value {
+    vm.stack.last().cloned().unwrap()
+}
+
+#[test]
+fn echo_over_localhost() {
+    let (mut vm, _) = common::vm();
+    call(&mut vm, "net.listen", vec![string("127.0.0.1:0")]).unwrap();
+    let listener = top(&vm);
+    // too far away for a deadline, so accept just blocks
+    call(
+        &mut vm,
+        "net.set_timeout",
+        vec![ir::Value::Number(1e18), listener.clone()],
+    )
+    .unwrap();
+    let address = match call_one(&mut vm, "net.local_addr", vec![listener.clone()]) {
+        Ok(ir::Value::String(address)) => address.to_string(),
+        other => panic!("expected an address, got {other:?}"),
+    };
+
+    let client = thread::spawn(move || {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream.write_all("héllo\n".as_bytes()).unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        line
+    });
+
+    call(&mut vm, "net.accept", vec![listener]).unwrap();
+    let socket = top(&vm);
+    let line = call_one(&mut vm, "net.read_line", vec![socket.clone()]).unwrap();
+    assert_eq!(line, string("héllo"));
+    call(
+        &mut vm,
+        "net.write",
+        vec![string("héllo\n"), socket.clone()],
+    )
+    .unwrap();
+    call(&mut vm, "net.close", vec![socket]).unwrap();
+
+    assert_eq!(client.join().unwrap(), "héllo\n");
+}
+
+#[test]
+fn set_timeout_rejects_invalid_durations() {
+    let (mut vm, _) = common::vm();
+    call(&mut vm, "net.listen", vec![string("127.0.0.1:0")]).unwrap();
+    let listener = top(&vm);
+    for ms in [f64::INFINITY, 1e300] {
+        assert!(matches!(
+            call(
+                &mut vm,
+                "net.set_timeout",
+                vec![ir::Value::Number(ms), listener.clone()]
+            ),
+            Err(VMError::RuntimeException(_))
+        ));
+    }
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{call_one, int};
+use enalang_ir as ir;
+use enalang_vm::{collections::Map, machine::VMError, number};
+
+fn big(st: &str) -> ir::Value {
+    ir::Value::integer(st.parse().unwrap())
+}
+
+#[test]
+fn integral_floats_find_integer_keys() {
+    let mut map = Map::new();
+    map.insert(int(1), int(10)).unwrap();
+    map.insert(big("1180591620717411303424"), int(20)).unwrap();
+
+    assert_eq!(map.get(&ir::Value::Number(1.0)).unwrap(), Some(&int(10)));
+    assert_eq!(
+        map.get(&ir::Value::Number(2f64.powi(70))).unwrap(),
+        Some(&int(20))
+    );
+    assert_eq!(map.get(&ir::Value::Number(1.5)).unwrap(), None);
+}
+
+#[test]
+fn equal_numbers_hash_the_same() {
+    let pairs = [
+        (int(1), ir::Value::Number(1.0)),
+        (int(-7), ir::Value::Number(-7.0)),
+        (
+            big("1180591620717411303424"),
+            ir::Value::Number(2f64.powi(70)),
+        ),
+    ];
+    for (a, b) in pairs {
+        assert!(number::values_equal(&a, &b));
+        assert_eq!(a.get_hash(), b.get_hash());
+    }
+
+    // 2^53 + 1 is rounded to 2^53 as a float, but the integers differ
+    let a = int((1 << 53) + 1);
+    let b = ir::Value::Number(2f64.powi(53));
+    assert!(!number::values_equal(&a, &b));
+}
+
+#[test]
+fn pow_rejects_huge_results() {
+    let (mut vm, _) = common::vm();
+    assert_eq!(
+        call_one(&mut vm, "pow", vec![int(10), int(2)]),
+        Ok(int(1024))
+    );
+    assert_eq!(
+        call_one(&mut vm, "pow", vec![int(100000000000), int(2)]),
+        Err(number::too_large())
+    );
+    assert_eq!(
+        call_one(&mut vm, "pow", vec![int(100000000000), int(1)]),
+        Ok(int(1))
+    );
+    assert!(matches!(
+        call_one(&mut vm, "pow", vec![int(3), int(0)]),
+        Ok(ir::Value::Integer(0))
+    ));
+}
+
+#[test]
+fn integer_modulo_zero_is_an_error() {
+    let (mut vm, _) = common::vm();
+    assert_eq!(
+        call_one(&mut vm, "math.mod", vec![int(3), int(-7)]),
+        Ok(int(2))
+    );
+    assert_eq!(
+        call_one(&mut vm, "math.mod", vec![int(0), int(7)]),
+        Err(VMError::DivisionByZero)
+    );
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "math.mod",
+            vec![int(0), big("1180591620717411303424")]
+        ),
+        Err(VMError::DivisionByZero)
+    );
+    assert!(matches!(
+        call_one(&mut vm, "math.mod", vec![ir::Value::Number(0.0), int(7)]),
+        Ok(ir::Value::Number(n)) if n.is_nan()
+    ));
+}
+
+#[test]
+fn shift_left_rejects_huge_results() {
+    let (mut vm, _) = common::vm();
+    assert_eq!(call_one(&mut vm, "<<", vec![int(2), int(4)]), Ok(int(16)));
+    assert_eq!(
+        call_one(&mut vm, "<<", vec![int(99999999999999), int(0)]),
+        Ok(int(0))
+    );
+    assert_eq!(
+        call_one(&mut vm, "<<", vec![int(99999999999999), int(1)]),
+        Err(number::too_large())
+    );
+}
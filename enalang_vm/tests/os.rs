@@ -0,0 +1,211 @@
+mod common;
+
+use common::{call, call_one, int, string};
+use enalang_ir as ir;
+use enalang_vm::machine::{VMError, VMOptions, VM};
+
+fn top(vm: &VM) -> ir::Value {
+    vm.stack.last().cloned().unwrap()
+}
+
+fn array(vm: &mut VM, values: &[&str]) -> ir::Value {
+    call(vm, "array.new", vec![]).unwrap();
+    let array = top(vm);
+    for value in values {
+        call(vm, "array.push", vec![string(value), array.clone()]).unwrap();
+    }
+    array
+}
+
+fn get(vm: &mut VM, key: &str, map: &ir::Value) -> ir::Value {
+    call_one(vm, "map.get", vec![string(key), map.clone()]).unwrap()
+}
+
+fn shell(vm: &mut VM, script: &str) -> Result<ir::Value, VMError> {
+    let args = array(vm, &["-c", script]);
+    call(vm, "ena.vm.os.exec", vec![args, string("sh")])?;
+    Ok(top(vm))
+}
+
+#[test]
+fn exec_is_blocked_without_permission() {
+    let mut options = VMOptions::new();
+    options.allow_subprocess = false;
+    let (mut vm, _) = common::vm_with_options(enalang_vm::native::NativeGroup::new(""), options);
+    assert_eq!(
+        shell(&mut vm, "touch should-not-exist"),
+        Err(VMError::SubprocessNotAllowed)
+    );
+
+    call(&mut vm, "map.new", vec![]).unwrap();
+    let options = top(&vm);
+    let args = array(&mut vm, &[]);
+    assert_eq!(
+        call(
+            &mut vm,
+            "ena.vm.os.exec_with",
+            vec![options, args, string("true")]
+        ),
+        Err(VMError::SubprocessNotAllowed)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn exec_captures_status_and_output() {
+    let (mut vm, _) = common::vm();
+    let result = shell(&mut vm, "echo out; echo err >&2; exit 3").unwrap();
+    assert_eq!(get(&mut vm, "status", &result), int(3));
+    assert_eq!(get(&mut vm, "stdout", &result), string("out\n"));
+    assert_eq!(get(&mut vm, "stderr", &result), string("err\n"));
+}
+
+#[cfg(unix)]
+#[test]
+fn exec_with_passes_options() {
+    let (mut vm, _) = common::vm();
+    call(&mut vm, "map.new", vec![]).unwrap();
+    let env = top(&vm);
+    call(
+        &mut vm,
+        "map.insert",
+        vec![string("value"), string("ENA_TEST_VAR"), env.clone()],
+    )
+    .unwrap();
+    call(&mut vm, "map.new", vec![]).unwrap();
+    let options = top(&vm);
+    call(
+        &mut vm,
+        "map.insert",
+        vec![env, string("env"), options.clone()],
+    )
+    .unwrap();
+    call(
+        &mut vm,
+        "map.insert",
+        vec![string("input"), string("stdin"), options.clone()],
+    )
+    .unwrap();
+
+    let args = array(&mut vm, &["-c", "cat; echo \" $ENA_TEST_VAR\""]);
+    call(
+        &mut vm,
+        "ena.vm.os.exec_with",
+        vec![options, args, string("sh")],
+    )
+    .unwrap();
+    let result = top(&vm);
+    assert_eq!(get(&mut vm, "status", &result), int(0));
+    assert_eq!(get(&mut vm, "stdout", &result), string("input value\n"));
+}
+
+#[test]
+fn exec_reports_missing_programs() {
+    let (mut vm, _) = common::vm();
+    let args = array(&mut vm, &[]);
+    assert!(matches!(
+        call(&mut vm, "ena.vm.os.exec", vec![args, string("ena-no-such-program")]),
+        Err(VMError::RuntimeException(ir::Value::String(message)))
+            if message.starts_with("ena-no-such-program: ")
+    ));
+}
+
+fn exception(result: Result<(), VMError>) -> String {
+    match result {
+        Err(VMError::RuntimeException(ir::Value::String(st))) => st.to_string(),
+        other => panic!("expected an exception, got {other:?}"),
+    }
+}
+
+#[test]
+fn environment_variables() {
+    let (mut vm, _) = common::vm();
+    let name = format!("ENA_TEST_ENV_{}", std::process::id());
+    assert_eq!(
+        call_one(&mut vm, "ena.vm.os.get_env", vec![string(&name)]),
+        Ok(ir::Value::Null)
+    );
+
+    call(
+        &mut vm,
+        "ena.vm.os.set_env",
+        vec![string("a value"), string(&name)],
+    )
+    .unwrap();
+    assert_eq!(std::env::var(&name).unwrap(), "a value");
+    assert_eq!(
+        call_one(&mut vm, "ena.vm.os.get_env", vec![string(&name)]),
+        Ok(string("a value"))
+    );
+
+    let total = match call_one(&mut vm, "ena.vm.os.env", vec![]) {
+        Ok(ir::Value::Integer(total)) => total as usize,
+        other => panic!("expected a count, got {other:?}"),
+    };
+    let pairs = vm.stack.split_off(vm.stack.len() - total * 2);
+    assert!(pairs
+        .chunks(2)
+        .any(|pair| pair == [string(&name), string("a value")]));
+
+    call(&mut vm, "ena.vm.os.unset_env", vec![string(&name)]).unwrap();
+    assert!(std::env::var(&name).is_err());
+    assert_eq!(
+        call_one(&mut vm, "ena.vm.os.get_env", vec![string(&name)]),
+        Ok(ir::Value::Null)
+    );
+}
+
+#[test]
+fn invalid_environment_variables() {
+    let (mut vm, _) = common::vm();
+    for name in ["", "A=B", "A\0B"] {
+        assert_eq!(
+            exception(call(
+                &mut vm,
+                "ena.vm.os.set_env",
+                vec![string("x"), string(name)]
+            )),
+            format!("invalid environment variable `{name}`")
+        );
+        assert_eq!(
+            exception(call(&mut vm, "ena.vm.os.unset_env", vec![string(name)])),
+            format!("invalid environment variable `{name}`")
+        );
+    }
+    assert!(exception(call(
+        &mut vm,
+        "ena.vm.os.set_env",
+        vec![string("a\0b"), string("ENA_TEST_NUL")]
+    ))
+    .starts_with("invalid environment variable"));
+    assert_eq!(
+        call(&mut vm, "ena.vm.os.get_env", vec![int(1)]),
+        Err(VMError::ExpectedString)
+    );
+}
+
+#[test]
+fn paths() {
+    let (mut vm, _) = common::vm();
+    assert_eq!(
+        call_one(&mut vm, "ena.vm.os.temp_dir", vec![]),
+        Ok(common::path(&std::env::temp_dir()))
+    );
+    assert_eq!(
+        call_one(&mut vm, "ena.vm.os.executable_path", vec![]),
+        Ok(common::path(&std::env::current_exe().unwrap()))
+    );
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "ena.vm.os.find_executable",
+            vec![string("ena-no-such-program")]
+        ),
+        Ok(ir::Value::Null)
+    );
+    #[cfg(unix)]
+    assert!(matches!(
+        call_one(&mut vm, "ena.vm.os.find_executable", vec![string("sh")]),
+        Ok(ir::Value::String(path)) if path.ends_with("/sh")
+    ));
+}
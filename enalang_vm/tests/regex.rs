@@ -0,0 +1,127 @@
+mod common;
+
+use common::{call, call_one, int, string};
+use enalang_ir as ir;
+use enalang_vm::{
+    machine::{VMError, VM},
+    native::NativeGroup,
+};
+use flexstr::ToLocalStr;
+
+fn popped(vm: &mut VM, count: usize) -> Vec<ir::Value> {
+    vm.stack.split_off(vm.stack.len() - count)
+}
+
+#[test]
+fn matching_and_finding() {
+    let (mut vm, _) = common::vm();
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "regex.is_match",
+            vec![string("^a+b$"), string("aaab")]
+        ),
+        Ok(ir::Value::Boolean(true))
+    );
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "regex.is_match",
+            vec![string("^a+b$"), string("ab!")]
+        ),
+        Ok(ir::Value::Boolean(false))
+    );
+
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "regex.find",
+            vec![string(r"(\d+)-(x)?(\d+)"), string("from 12-34 on")]
+        ),
+        Ok(int(4))
+    );
+    assert_eq!(
+        popped(&mut vm, 4),
+        vec![string("12-34"), string("12"), ir::Value::Null, string("34")]
+    );
+    assert_eq!(
+        call_one(&mut vm, "regex.find", vec![string(r"\d"), string("none")]),
+        Ok(int(0))
+    );
+
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "regex.find_all",
+            vec![string(r"\d+"), string("1 22 333")]
+        ),
+        Ok(int(3))
+    );
+    assert_eq!(
+        popped(&mut vm, 3),
+        vec![string("1"), string("22"), string("333")]
+    );
+    assert!(vm.stack.is_empty());
+}
+
+#[test]
+fn replacing() {
+    let (mut vm, _) = common::vm();
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "regex.replace",
+            vec![
+                string("$2 $1"),
+                string(r"(\w+) (\w+)"),
+                string("hello world")
+            ]
+        ),
+        Ok(string("world hello"))
+    );
+}
+
+#[test]
+fn invalid_patterns_are_errors() {
+    let (mut vm, _) = common::vm();
+    for name in ["regex.is_match", "regex.find", "regex.find_all"] {
+        assert!(
+            matches!(
+                call(&mut vm, name, vec![string("(unclosed"), string("text")]),
+                Err(VMError::InvalidRegex(_))
+            ),
+            "{name}"
+        );
+    }
+    assert!(matches!(
+        call(
+            &mut vm,
+            "regex.replace",
+            vec![string(""), string("[z-a]"), string("text")]
+        ),
+        Err(VMError::InvalidRegex(_))
+    ));
+}
+
+#[test]
+fn invalid_patterns_can_be_caught() {
+    let mut group = NativeGroup::new("test");
+    group
+        .add_native("bad_pattern", |ctx| {
+            ctx.vm.push(string("(unclosed"))?;
+            ctx.vm.push(string("text"))?;
+            ctx.vm
+                .run_block(&"regex.is_match".to_local_str())
+                .map(|_| ())
+        })
+        .unwrap();
+    let (mut vm, _) = common::vm_with(group);
+
+    let block = ir::Value::Block("test.bad_pattern".to_local_str());
+    match call_one(&mut vm, "try", vec![block]) {
+        Ok(ir::Value::Exception(err)) => {
+            assert!(matches!(*err, ir::Value::String(st) if st.starts_with("InvalidRegex(")))
+        }
+        other => panic!("expected an exception, got {other:?}"),
+    }
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::{call, call_one, int, string};
+use enalang_ir as ir;
+use enalang_vm::machine::VMError;
+
+fn too_long() -> Result<ir::Value, VMError> {
+    Err(VMError::RuntimeException(string("string is too long")))
+}
+
+#[test]
+fn split() {
+    let (mut vm, _) = common::vm();
+    call(&mut vm, "string.split", vec![string("→"), string("a→bé→")]).unwrap();
+    assert_eq!(
+        vm.stack,
+        vec![string("a"), string("bé"), string(""), int(3)]
+    );
+}
+
+#[test]
+fn replace() {
+    let (mut vm, _) = common::vm();
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "string.replace",
+            vec![string("ü"), string("u"), string("uber uns")]
+        ),
+        Ok(string("über üns"))
+    );
+}
+
+#[test]
+fn pad() {
+    let (mut vm, _) = common::vm();
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "string.pad_start",
+            vec![string("·-"), int(6), string("ñu")]
+        ),
+        Ok(string("·-·-ñu"))
+    );
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "string.pad_end",
+            vec![string("日"), int(3), string("本")]
+        ),
+        Ok(string("本日日"))
+    );
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "string.pad_end",
+            vec![string("x"), int(2), string("long")]
+        ),
+        Ok(string("long"))
+    );
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "string.pad_start",
+            vec![string("x"), int(i64::MAX), string("")]
+        ),
+        too_long()
+    );
+}
+
+#[test]
+fn format() {
+    let (mut vm, _) = common::vm();
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "string.format",
+            vec![string("ä"), int(2), string("{} {{{}}} ✓")]
+        ),
+        Ok(string("ä {2} ✓"))
+    );
+}
+
+#[test]
+fn repeat() {
+    let (mut vm, _) = common::vm();
+    assert_eq!(
+        call_one(&mut vm, "string.repeat", vec![int(3), string("hé")]),
+        Ok(string("héhéhé"))
+    );
+    assert_eq!(
+        call_one(&mut vm, "string.repeat", vec![int(0), string("hé")]),
+        Ok(string(""))
+    );
+    assert_eq!(
+        call_one(
+            &mut vm,
+            "string.repeat",
+            vec![int(99999999999999), string("ab")]
+        ),
+        too_long()
+    );
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::{call, call_one, int, string};
+use enalang_ir as ir;
+use enalang_vm::machine::VMError;
+
+#[test]
+fn sleep_rejects_invalid_durations() {
+    let (mut vm, _) = common::vm();
+    assert_eq!(call(&mut vm, "time.sleep", vec![int(1)]), Ok(()));
+    assert_eq!(call(&mut vm, "time.sleep", vec![int(-5)]), Ok(()));
+    for ms in [f64::INFINITY, 1e300] {
+        assert!(matches!(
+            call(&mut vm, "time.sleep", vec![ir::Value::Number(ms)]),
+            Err(VMError::RuntimeException(_))
+        ));
+    }
+}
+
+fn parse(st: &str) -> Result<ir::Value, VMError> {
+    let (mut vm, _) = common::vm();
+    call_one(&mut vm, "time.parse", vec![string(st)])
+}
+
+fn format(timestamp: ir::Value) -> String {
+    let (mut vm, _) = common::vm();
+    match call_one(&mut vm, "time.format", vec![timestamp]) {
+        Ok(ir::Value::String(st)) => st.to_string(),
+        other => panic!("expected a string, got {other:?}"),
+    }
+}
+
+#[test]
+fn leap_days() {
+    assert_eq!(parse("2000-02-29"), Ok(int(951782400)));
+    assert_eq!(parse("2024-02-29T12:00Z"), Ok(int(1709208000)));
+    assert_eq!(format(int(951782400)), "2000-02-29T00:00:00Z");
+    // centuries are only leap years when divisible by 400
+    for st in ["1900-02-29", "2023-02-29", "2100-02-29", "2000-02-30"] {
+        assert!(
+            matches!(parse(st), Err(VMError::RuntimeException(_))),
+            "{st}"
+        );
+    }
+}
+
+#[test]
+fn offsets() {
+    for st in [
+        "2023-11-14T22:13:20Z",
+        "2023-11-15T00:13:20+02:00",
+        "2023-11-15T00:13:20+0200",
+        "2023-11-14T17:13:20-05:00",
+        "2023-11-14 22:13:20",
+    ] {
+        assert_eq!(parse(st), Ok(int(1700000000)), "{st}");
+    }
+    assert!(matches!(
+        parse("2023-11-14T22:13:20+24:00"),
+        Err(VMError::RuntimeException(_))
+    ));
+}
+
+#[test]
+fn fractional_seconds() {
+    assert_eq!(
+        parse("2023-11-14T22:13:20.5Z"),
+        Ok(ir::Value::Number(1700000000.5))
+    );
+    assert_eq!(
+        parse("2023-11-14T22:13:20,250+00:00"),
+        Ok(ir::Value::Number(1700000000.25))
+    );
+    assert_eq!(
+        format(ir::Value::Number(1700000000.25)),
+        "2023-11-14T22:13:20.250Z"
+    );
+    assert!(matches!(
+        parse("2023-11-14T22:13:20.Z"),
+        Err(VMError::RuntimeException(_))
+    ));
+}
+
+#[test]
+fn timestamps_before_1970() {
+    assert_eq!(format(int(-1)), "1969-12-31T23:59:59Z");
+    assert_eq!(parse("1969-12-31T23:59:59Z"), Ok(int(-1)));
+    assert_eq!(format(ir::Value::Number(-1.5)), "1969-12-31T23:59:58.500Z");
+    assert_eq!(parse("1900-01-01"), Ok(int(-2208988800)));
+    assert_eq!(format(int(-2208988800)), "1900-01-01T00:00:00Z");
+    assert_eq!(format(int(-62135596800)), "0001-01-01T00:00:00Z");
+}